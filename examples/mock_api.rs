@@ -0,0 +1,155 @@
+//! A tiny mock of the REST API for manual and headless browser tests.
+//!
+//! Serves `GET /api/movies` from `mock_api/movies.json` and the list endpoints on the default `API_URL`:
+//! `cargo make mock_api` (or `cargo run --example mock_api`).
+//! Lists start as `mock_api/lists.json`; changes are kept in memory until the mock is stopped.
+
+use serde_json::{Map, Value};
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+const ADDRESS: &str = "127.0.0.1:8081";
+const MOVIES: &str = include_str!("mock_api/movies.json");
+const LISTS: &str = include_str!("mock_api/lists.json");
+
+/// Lists by id, in the format of `GET /api/lists`.
+type Lists = Map<String, Value>;
+/// Status line and JSON body.
+type Response = (&'static str, String);
+
+fn main() {
+    let mut lists = serde_json::from_str::<Lists>(LISTS).expect("valid mock lists");
+    let listener = TcpListener::bind(ADDRESS).expect("bind mock API address");
+    println!("Mock API listening on http://{}/api", ADDRESS);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(error) = respond(stream, &mut lists) {
+                    eprintln!("Cannot respond: {}", error);
+                }
+            },
+            Err(error) => eprintln!("Cannot accept a connection: {}", error),
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, lists: &mut Lists) -> std::io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let mut header = header.trim_end().splitn(2, ':');
+        match (header.next(), header.next()) {
+            (Some(name), Some(value)) if name.eq_ignore_ascii_case("content-length") => {
+                content_length = value.trim().parse().unwrap_or_default();
+            },
+            (Some(""), None) => break,
+            _ => {},
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let segments = path.split('/').filter(|segment| !segment.is_empty()).collect::<Vec<_>>();
+    let (status, body) = match (method, segments.as_slice()) {
+        ("GET", ["api", "movies"]) => ("200 OK", MOVIES.to_owned()),
+        ("GET", ["api", "lists"]) => ("200 OK", Value::Object(lists.clone()).to_string()),
+        ("POST", ["api", "lists"]) => create_list(lists, body),
+        ("GET", ["api", "lists", list_id]) => match lists.get(*list_id) {
+            Some(list) => ("200 OK", list.to_string()),
+            None => not_found(),
+        },
+        ("PATCH", ["api", "lists", list_id]) => update_list(lists, list_id, body),
+        ("DELETE", ["api", "lists", list_id]) => match lists.remove(*list_id) {
+            Some(_) => no_content(),
+            None => not_found(),
+        },
+        ("POST", ["api", "lists", list_id, "movies"]) => add_list_movies(lists, list_id, body),
+        ("OPTIONS", _) => no_content(),
+        _ => not_found(),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+        Access-Control-Allow-Origin: *\r\n\
+        Access-Control-Allow-Methods: GET, POST, PATCH, PUT, DELETE\r\n\
+        Access-Control-Allow-Headers: Content-Type, If-Match\r\n\
+        Content-Type: application/json\r\n\
+        Content-Length: {}\r\n\
+        Connection: close\r\n\
+        \r\n\
+        {}",
+        status,
+        body.len(),
+        body,
+    )
+}
+
+fn no_content() -> Response {
+    ("204 No Content", String::new())
+}
+
+fn not_found() -> Response {
+    ("404 Not Found", String::new())
+}
+
+fn bad_request() -> Response {
+    ("400 Bad Request", String::new())
+}
+
+/// `{ "id": .., "name": .., "movie_ids": [..] }`; `movie_ids` are optional.
+fn create_list(lists: &mut Lists, new_list: Value) -> Response {
+    let mut list = match new_list {
+        Value::Object(list) => list,
+        _ => return bad_request(),
+    };
+    let list_id = match list.remove("id") {
+        Some(Value::String(list_id)) => list_id,
+        _ => return bad_request(),
+    };
+    list.entry("movie_ids").or_insert_with(|| Value::Array(Vec::new()));
+    lists.insert(list_id, Value::Object(list));
+    ("201 Created", String::new())
+}
+
+/// Replaces only the sent fields - e.g. a rename sends just the `name`.
+fn update_list(lists: &mut Lists, list_id: &str, changes: Value) -> Response {
+    match (lists.get_mut(list_id), changes) {
+        (Some(Value::Object(list)), Value::Object(changes)) => {
+            list.extend(changes);
+            no_content()
+        },
+        (None, _) => not_found(),
+        _ => bad_request(),
+    }
+}
+
+/// Appends the movies to the end of the list, skipping movies already in it.
+fn add_list_movies(lists: &mut Lists, list_id: &str, movie_ids: Value) -> Response {
+    let movie_ids = match movie_ids {
+        Value::Array(movie_ids) => movie_ids,
+        _ => return bad_request(),
+    };
+    let list_movie_ids = lists
+        .get_mut(list_id)
+        .and_then(|list| list.get_mut("movie_ids"))
+        .and_then(Value::as_array_mut);
+    let list_movie_ids = match list_movie_ids {
+        Some(list_movie_ids) => list_movie_ids,
+        None => return not_found(),
+    };
+    for movie_id in movie_ids {
+        if !list_movie_ids.contains(&movie_id) {
+            list_movie_ids.push(movie_id);
+        }
+    }
+    no_content()
+}
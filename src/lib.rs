@@ -10,10 +10,17 @@ use seed_style::*;
 mod page;
 
 const MOVIES: &str = "movies";
+const LISTS: &str = "lists";
 const TIME_TRACKER: &str = "time_tracker";
 const TIME_BLOCKS: &str = "time_blocks";
 const SETTINGS: &str = "settings";
 
+// Override during compilation - e.g. `API_URL=http://localhost:3000/api cargo make build`.
+const API_URL: &str = match option_env!("API_URL") {
+    Some(api_url) => api_url,
+    None => "http://localhost:8081/api",
+};
+
 // ------ ------
 //     Init
 // ------ ------
@@ -161,19 +168,40 @@ struct User {
 // ------ Page ------
 
 enum Page {
-    Home,
+    Home(page::home::Model),
     Movies(page::movies::Model),
+    MovieDetail(page::movie_detail::Model),
+    Lists(page::lists::Model),
+    ListDetail(page::list_detail::Model),
     Settings(page::settings::Model),
     NotFound,
 }
 
 impl Page {
     fn init(mut url: Url, orders: &mut impl Orders<Msg>) -> Self {
+        let base_url = url.to_base_url();
         match url.remaining_path_parts().as_slice() {
-            [] => Self::Home,
+            [] => Self::Home(
+                page::home::init(base_url, &mut orders.proxy(Msg::HomeMsg))
+            ),
             [MOVIES] => Self::Movies(
                 page::movies::init(url, &mut orders.proxy(Msg::MoviesMsg))
             ),
+            [MOVIES, movie_id] => match page::movies::MovieId::from_string(movie_id) {
+                Ok(movie_id) => Self::MovieDetail(
+                    page::movie_detail::init(base_url, movie_id, &mut orders.proxy(Msg::MovieDetailMsg))
+                ),
+                Err(_) => Self::NotFound,
+            },
+            [LISTS] => Self::Lists(
+                page::lists::init(base_url, &mut orders.proxy(Msg::ListsMsg))
+            ),
+            [LISTS, list_id] => match page::lists::ListId::from_string(list_id) {
+                Ok(list_id) => Self::ListDetail(
+                    page::list_detail::init(base_url, list_id, &mut orders.proxy(Msg::ListDetailMsg))
+                ),
+                Err(_) => Self::NotFound,
+            },
             [SETTINGS] => Self::Settings(
                 page::settings::init(url, &mut orders.proxy(Msg::SettingsMsg))
             ),
@@ -194,6 +222,20 @@ impl<'a> Urls<'a> {
     fn movies(self) -> Url {
         self.base_url().add_path_part(MOVIES)
     }
+    fn movies_with_filters(self, filters: &page::movies::Filters) -> Url {
+        let mut url = self.movies();
+        filters.write_url_search(url.search_mut());
+        url
+    }
+    fn movie(self, movie_id: page::movies::MovieId) -> Url {
+        self.movies().add_path_part(movie_id.to_string())
+    }
+    fn lists(self) -> Url {
+        self.base_url().add_path_part(LISTS)
+    }
+    fn list(self, list_id: page::lists::ListId) -> Url {
+        self.lists().add_path_part(list_id.to_string())
+    }
     fn settings(self) -> Url {
         self.base_url().add_path_part(SETTINGS)
     }
@@ -210,7 +252,11 @@ enum Msg {
 
     // ------ pages ------
 
+    HomeMsg(page::home::Msg),
     MoviesMsg(page::movies::Msg),
+    MovieDetailMsg(page::movie_detail::Msg),
+    ListsMsg(page::lists::Msg),
+    ListDetailMsg(page::list_detail::Msg),
     SettingsMsg(page::settings::Msg),
 }
 
@@ -228,11 +274,31 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
 
         // ------ pages ------
 
+        Msg::HomeMsg(msg) => {
+            if let Page::Home(model) = &mut model.page {
+                page::home::update(msg, model, &mut orders.proxy(Msg::HomeMsg))
+            }
+        }
         Msg::MoviesMsg(msg) => {
             if let Page::Movies(model) = &mut model.page {
                 page::movies::update(msg, model, &mut orders.proxy(Msg::MoviesMsg))
             }
         }
+        Msg::MovieDetailMsg(msg) => {
+            if let Page::MovieDetail(model) = &mut model.page {
+                page::movie_detail::update(msg, model, &mut orders.proxy(Msg::MovieDetailMsg))
+            }
+        }
+        Msg::ListsMsg(msg) => {
+            if let Page::Lists(model) = &mut model.page {
+                page::lists::update(msg, model, &mut orders.proxy(Msg::ListsMsg))
+            }
+        }
+        Msg::ListDetailMsg(msg) => {
+            if let Page::ListDetail(model) = &mut model.page {
+                page::list_detail::update(msg, model, &mut orders.proxy(Msg::ListDetailMsg))
+            }
+        }
         Msg::SettingsMsg(msg) => {
             if let Page::Settings(model) = &mut model.page {
                 page::settings::update(msg, model, &mut orders.proxy(Msg::SettingsMsg))
@@ -248,13 +314,13 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
 fn view(model: &Model) -> Vec<Node<Msg>> {
     vec![
         view_navbar(model.menu_visible, &model.base_url, model.ctx.user.as_ref(), &model.page),
-        view_content(&model.page),
+        view_content(&model.base_url, &model.page),
     ]
 }
 
 // ----- view_content ------
 
-fn view_content(page: &Page) -> Node<Msg> {
+fn view_content(base_url: &Url, page: &Page) -> Node<Msg> {
     div![
         C!["container"],
         s()
@@ -273,8 +339,11 @@ fn view_content(page: &Page) -> Node<Msg> {
             .only_and_above(Breakpoint::FullHD)
             .max_w(px(1344)),
         match page {
-            Page::Home => page::home::view(),
-            Page::Movies(model) => page::movies::view(model).map_msg(Msg::MoviesMsg),
+            Page::Home(model) => page::home::view(model).map_msg(Msg::HomeMsg),
+            Page::Movies(model) => page::movies::view(model, base_url).map_msg(Msg::MoviesMsg),
+            Page::MovieDetail(model) => page::movie_detail::view(model).map_msg(Msg::MovieDetailMsg),
+            Page::Lists(model) => page::lists::view(model).map_msg(Msg::ListsMsg),
+            Page::ListDetail(model) => page::list_detail::view(model).map_msg(Msg::ListDetailMsg),
             Page::Settings(model) => page::settings::view(model).map_msg(Msg::SettingsMsg),
             Page::NotFound => page::not_found::view(),
         }
@@ -439,47 +508,57 @@ fn view_navbar_menu_start(base_url: &Url, page: &Page) -> Node<Msg> {
             .mr(CssMarginRight::Auto)
             .align_items(CssAlignItems::Stretch)
             .display(CssDisplay::Flex),
-        {
-            let is_active = matches!(page, Page::Movies(_));
-            a![
-                C!["navbar-item", /*@TODO: Remove: "is-tab", IF!(is_active => "is-active"),*/],
-                s()
-                    .display(CssDisplay::Block)
-                    .border_bottom("1px solid transparent")
-                    .min_h(rem(3.25))
-                    .pb("calc(.5rem - 1px)")
-                    .cursor(CssCursor::Pointer)
-                    .flex_grow("0")
-                    .flex_shrink("0")
-                    .color("#4a4a4a")
-                    .line_height("1.5")
-                    .pt(rem(0.5))
-                    .px(rem(0.75))
-                    .position(CssPosition::Relative),
-                s()
-                    .focus()
-                    .hover()
-                    .bg_color("transparent")
-                    .border_bottom_color("#3273dc")
-                    .color("#3273dc"),
-                if is_active {
-                    s()
-                        .bg_color("transparent")
-                        .border_bottom_color("#3273dc")
-                        .color("#3273dc")
-                        .border_bottom_width(px(3))
-                        .pb("calc(.5rem - 3px)")
-                } else {
-                    s()
-                },
-                s()
-                    .only_and_above(Breakpoint::Desktop)
-                    .align_items(CssAlignItems::Center)
-                    .display(CssDisplay::Flex),
-                attrs!{At::Href => Urls::new(base_url).movies()},
-                "Movies",
-            ]
-        }
+        view_navbar_item(
+            matches!(page, Page::Movies(_) | Page::MovieDetail(_)),
+            Urls::new(base_url).movies(),
+            "Movies",
+        ),
+        view_navbar_item(
+            matches!(page, Page::Lists(_) | Page::ListDetail(_)),
+            Urls::new(base_url).lists(),
+            "Lists",
+        ),
+    ]
+}
+
+fn view_navbar_item(is_active: bool, href: Url, label: &str) -> Node<Msg> {
+    a![
+        C!["navbar-item", /*@TODO: Remove: "is-tab", IF!(is_active => "is-active"),*/],
+        s()
+            .display(CssDisplay::Block)
+            .border_bottom("1px solid transparent")
+            .min_h(rem(3.25))
+            .pb("calc(.5rem - 1px)")
+            .cursor(CssCursor::Pointer)
+            .flex_grow("0")
+            .flex_shrink("0")
+            .color("#4a4a4a")
+            .line_height("1.5")
+            .pt(rem(0.5))
+            .px(rem(0.75))
+            .position(CssPosition::Relative),
+        s()
+            .focus()
+            .hover()
+            .bg_color("transparent")
+            .border_bottom_color("#3273dc")
+            .color("#3273dc"),
+        if is_active {
+            s()
+                .bg_color("transparent")
+                .border_bottom_color("#3273dc")
+                .color("#3273dc")
+                .border_bottom_width(px(3))
+                .pb("calc(.5rem - 3px)")
+        } else {
+            s()
+        },
+        s()
+            .only_and_above(Breakpoint::Desktop)
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex),
+        attrs!{At::Href => href},
+        label,
     ]
 }
 
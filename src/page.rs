@@ -1,4 +1,7 @@
 pub mod home;
 pub mod movies;
+pub mod movie_detail;
+pub mod lists;
+pub mod list_detail;
 pub mod settings;
 pub mod not_found;
@@ -1,5 +1,346 @@
 use seed::{prelude::*, *};
 
-pub fn view<Ms>() -> Node<Ms> {
-    div!["Home view"]
+use seed_style::{pc, px, rem};
+use seed_style::*;
+
+use chrono::prelude::*;
+
+use std::collections::BTreeMap;
+
+use super::movies::{self, recommendations, Filters, Movie, MovieId};
+use recommendations::Recommendation;
+
+mod stats;
+use stats::Stats;
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+const CHART_WIDTH: f64 = 600.;
+const CHART_HEIGHT: f64 = 200.;
+/// Space for month labels under the bars.
+const CHART_LABELS_HEIGHT: f64 = 20.;
+
+const RECOMMENDATIONS: usize = 5;
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(base_url: Url, orders: &mut impl Orders<Msg>) -> Model {
+    orders.perform_cmd(async { Msg::MoviesFetched(movies::request_movies().await) });
+
+    Model {
+        base_url,
+        errors: Vec::new(),
+
+        dashboard: RemoteData::Loading,
+    }
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    base_url: Url,
+    errors: Vec<FetchError>,
+
+    dashboard: RemoteData<Dashboard>,
+}
+
+struct Dashboard {
+    stats: Stats,
+    recommendations: Vec<Recommendation>,
+    movies: BTreeMap<MovieId, Movie>,
+}
+
+enum RemoteData<T> {
+    NotAsked,
+    Loading,
+    Loaded(T),
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    MoviesFetched(fetch::Result<BTreeMap<MovieId, Movie>>),
+    ClearErrors,
+}
+
+pub fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::MoviesFetched(Ok(movies)) => {
+            model.dashboard = RemoteData::Loaded(Dashboard {
+                stats: Stats::new(&movies, Local::today().year()),
+                recommendations: recommendations::you_might_enjoy(&movies, RECOMMENDATIONS),
+                movies,
+            });
+        },
+        Msg::MoviesFetched(Err(fetch_error)) => {
+            model.dashboard = RemoteData::NotAsked;
+            model.errors.push(fetch_error);
+        },
+
+        Msg::ClearErrors => model.errors.clear(),
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        s()
+            .py(rem(1.5))
+            .px(rem(0.75)),
+        view_errors(&model.errors),
+        match &model.dashboard {
+            RemoteData::NotAsked => empty![],
+            RemoteData::Loading => view_message("Loading statistics..."),
+            RemoteData::Loaded(dashboard) if dashboard.stats.total_movies == 0 => div![
+                view_message("No movies yet."),
+                p![
+                    s()
+                        .text_align(CssTextAlign::Center),
+                    a![
+                        attrs!{At::Href => crate::Urls::new(&model.base_url).movies()},
+                        "Add your first movie",
+                    ],
+                ],
+            ],
+            RemoteData::Loaded(dashboard) => view_dashboard(dashboard, &model.base_url),
+        }
+    ]
+}
+
+fn view_errors(errors: &[FetchError]) -> Node<Msg> {
+    if errors.is_empty() {
+        return empty![];
+    }
+    div![
+        C!["notification"],
+        s()
+            .bg_color("#feecf0")
+            .color("#cc0f35")
+            .border_radius(px(4))
+            .mb(rem(1.5))
+            .py(rem(1.25))
+            .px(rem(1.5)),
+        ul![
+            errors.iter().map(|error| li![format!("{:?}", error)])
+        ],
+        button![
+            C!["button"],
+            s()
+                .mt(rem(0.75))
+                .cursor(CssCursor::Pointer),
+            "Clear errors",
+            ev(Ev::Click, |_| Msg::ClearErrors),
+        ]
+    ]
+}
+
+fn view_message(message: &str) -> Node<Msg> {
+    p![
+        s()
+            .color("#7a7a7a")
+            .text_align(CssTextAlign::Center)
+            .py(rem(3)),
+        message
+    ]
+}
+
+fn view_dashboard(dashboard: &Dashboard, base_url: &Url) -> Node<Msg> {
+    let stats = &dashboard.stats;
+    let year = Local::today().year();
+    div![
+        div![
+            s()
+                .display(CssDisplay::Grid)
+                .grid_template_columns("repeat(auto-fit, minmax(160px, 1fr))")
+                .raw(format!("grid-gap: {};", rem(1)).as_str())
+                .mb(rem(1.5)),
+            view_stat("Movies", stats.total_movies.to_string()),
+            view_stat(&format!("Watched in {}", year), stats.watched_this_year.to_string()),
+            view_stat("Hours watched", (stats.minutes_watched / 60).to_string()),
+            view_stat("Average rating", match stats.average_rating {
+                Some(average_rating) => format!("★ {:.1}", average_rating / 2.),
+                None => "–".to_owned(),
+            }),
+        ],
+        div![
+            s()
+                .display(CssDisplay::Grid)
+                .grid_template_columns("1fr 2fr")
+                .raw(format!("grid-gap: {};", rem(1.5)).as_str()),
+            view_top_genres(&stats.top_genres, base_url),
+            view_monthly_chart(&stats.watched_per_month, year),
+        ],
+        view_recommendations(&dashboard.recommendations, &dashboard.movies, base_url),
+    ]
+}
+
+fn view_stat(label: &str, value: String) -> Node<Msg> {
+    div![
+        s()
+            .border("1px solid #ededed")
+            .border_radius(px(4))
+            .p(rem(1)),
+        p![
+            s()
+                .color("#7a7a7a")
+                .font_size(rem(0.875)),
+            label,
+        ],
+        p![
+            s()
+                .font_size(rem(2))
+                .font_weight("600"),
+            value,
+        ],
+    ]
+}
+
+fn view_section_title(title: &str) -> Node<Msg> {
+    h2![
+        s()
+            .font_size(rem(1.25))
+            .font_weight("600")
+            .mb(rem(0.75)),
+        title,
+    ]
+}
+
+fn view_top_genres(top_genres: &[(String, usize)], base_url: &Url) -> Node<Msg> {
+    div![
+        view_section_title("Top genres"),
+        if top_genres.is_empty() {
+            p![
+                s()
+                    .color("#7a7a7a"),
+                "No genres yet.",
+            ]
+        } else {
+            ol![
+                s()
+                    .pl(rem(1.25))
+                    .raw("list-style: decimal;"),
+                top_genres.iter().map(|(genre, count)| {
+                    let filters = Filters { genre: Some(genre.clone()), ..Filters::default() };
+                    li![
+                        a![
+                            attrs!{At::Href => crate::Urls::new(base_url).movies_with_filters(&filters)},
+                            genre,
+                        ],
+                        span![
+                            s()
+                                .color("#7a7a7a")
+                                .ml(rem(0.5)),
+                            count.to_string(),
+                        ],
+                    ]
+                })
+            ]
+        },
+    ]
+}
+
+fn view_recommendations(
+    recommendations: &[Recommendation],
+    movies: &BTreeMap<MovieId, Movie>,
+    base_url: &Url,
+) -> Node<Msg> {
+    div![
+        s()
+            .mt(rem(1.5)),
+        view_section_title("You might enjoy"),
+        if recommendations.is_empty() {
+            p![
+                s()
+                    .color("#7a7a7a"),
+                "Rate more movies you've watched to get recommendations from your watchlist.",
+            ]
+        } else {
+            ul![
+                recommendations.iter().filter_map(|recommendation| {
+                    let movie = movies.get(&recommendation.movie_id)?;
+                    let because_of = movies.get(&recommendation.because_of)?;
+                    Some(li![
+                        s()
+                            .mb(rem(0.25)),
+                        a![
+                            attrs!{At::Href => crate::Urls::new(base_url).movie(recommendation.movie_id)},
+                            &movie.title,
+                        ],
+                        span![
+                            s()
+                                .color("#7a7a7a")
+                                .ml(rem(0.5)),
+                            format!("because you liked {}", because_of.title),
+                        ],
+                    ])
+                })
+            ]
+        },
+    ]
+}
+
+/// A bar chart scaled to the busiest month; the SVG scales with its container.
+fn view_monthly_chart(watched_per_month: &[usize; 12], year: i32) -> Node<Msg> {
+    let max_count = watched_per_month.iter().copied().max().unwrap_or_default().max(1);
+    let slot_width = CHART_WIDTH / MONTHS.len() as f64;
+    let bars_height = CHART_HEIGHT - CHART_LABELS_HEIGHT;
+    div![
+        view_section_title(&format!("Watched per month in {}", year)),
+        svg![
+            s()
+                .display(CssDisplay::Block)
+                .w(pc(100)),
+            attrs!{
+                At::ViewBox => format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT),
+                At::AriaLabel => format!("Movies watched per month in {}", year),
+            },
+            MONTHS.iter().zip(watched_per_month).enumerate().map(|(index, (month, count))| {
+                // Leaves a gap between bars and room for the count above the highest bar.
+                let height = (bars_height - 16.) * *count as f64 / max_count as f64;
+                let x = slot_width * index as f64;
+                let bar_top = bars_height - height;
+                g![
+                    rect![
+                        attrs!{
+                            At::X => x + slot_width * 0.15,
+                            At::Y => bar_top,
+                            At::Width => slot_width * 0.7,
+                            At::Height => height,
+                            At::Rx => 2,
+                            At::Fill => "#3273dc",
+                        },
+                    ],
+                    IF!(*count > 0 => text![
+                        attrs!{
+                            At::X => x + slot_width / 2.,
+                            At::Y => bar_top - 4.,
+                            At::TextAnchor => "middle",
+                            At::FontSize => 12,
+                            At::Fill => "#4a4a4a",
+                        },
+                        count.to_string(),
+                    ]),
+                    text![
+                        attrs!{
+                            At::X => x + slot_width / 2.,
+                            At::Y => CHART_HEIGHT - 4.,
+                            At::TextAnchor => "middle",
+                            At::FontSize => 12,
+                            At::Fill => "#7a7a7a",
+                        },
+                        *month,
+                    ],
+                ]
+            })
+        ],
+    ]
 }
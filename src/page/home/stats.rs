@@ -0,0 +1,187 @@
+use chrono::Datelike;
+
+use std::collections::BTreeMap;
+
+use super::super::movies::{Movie, MovieId, WatchStatus};
+
+const TOP_GENRES: usize = 5;
+
+/// Aggregations over the whole collection.
+pub struct Stats {
+    pub total_movies: usize,
+    pub watched_this_year: usize,
+    pub minutes_watched: u64,
+    /// Genres with their movie counts, the most common first.
+    pub top_genres: Vec<(String, usize)>,
+    /// Half-stars.
+    pub average_rating: Option<f64>,
+    /// Watches in each month of the year, January first.
+    pub watched_per_month: [usize; 12],
+}
+
+impl Stats {
+    pub fn new(movies: &BTreeMap<MovieId, Movie>, year: i32) -> Self {
+        Self {
+            total_movies: movies.len(),
+            watched_this_year: watched_in_year(movies, year),
+            minutes_watched: minutes_watched(movies),
+            top_genres: top_genres(movies, TOP_GENRES),
+            average_rating: average_rating(movies),
+            watched_per_month: watched_per_month(movies, year),
+        }
+    }
+}
+
+/// Rewatches count too; watched movies without watch history (e.g. imported ones) count once.
+pub fn watch_count(movie: &Movie) -> usize {
+    match movie.watch_history.len() {
+        0 if movie.watch_status == WatchStatus::Watched => 1,
+        count => count,
+    }
+}
+
+/// Movies watched at least once in the year.
+pub fn watched_in_year(movies: &BTreeMap<MovieId, Movie>, year: i32) -> usize {
+    movies
+        .values()
+        .filter(|movie| movie.watch_history.iter().any(|event| event.watched_on.year() == year))
+        .count()
+}
+
+/// Runtimes of all watches; movies without runtime are skipped.
+pub fn minutes_watched(movies: &BTreeMap<MovieId, Movie>) -> u64 {
+    movies
+        .values()
+        .filter_map(|movie| movie.runtime.map(|runtime| u64::from(runtime) * watch_count(movie) as u64))
+        .sum()
+}
+
+/// Ties are sorted by genre name.
+pub fn top_genres(movies: &BTreeMap<MovieId, Movie>, limit: usize) -> Vec<(String, usize)> {
+    let mut counts = BTreeMap::<&str, usize>::new();
+    for genre in movies.values().flat_map(|movie| &movie.genres) {
+        *counts.entry(genre).or_default() += 1;
+    }
+    let mut genres = counts.into_iter().collect::<Vec<_>>();
+    // The sort is stable and `counts` is sorted by name.
+    genres.sort_by(|(_, a), (_, b)| b.cmp(a));
+    genres
+        .into_iter()
+        .take(limit)
+        .map(|(genre, count)| (genre.to_owned(), count))
+        .collect()
+}
+
+/// The average of personal ratings in half-stars; unrated movies are skipped.
+pub fn average_rating(movies: &BTreeMap<MovieId, Movie>) -> Option<f64> {
+    let ratings = movies.values().filter_map(|movie| movie.rating).collect::<Vec<_>>();
+    if ratings.is_empty() {
+        return None;
+    }
+    let sum = ratings.iter().copied().map(f64::from).sum::<f64>();
+    Some(sum / ratings.len() as f64)
+}
+
+pub fn watched_per_month(movies: &BTreeMap<MovieId, Movie>, year: i32) -> [usize; 12] {
+    let mut months = [0; 12];
+    for event in movies.values().flat_map(|movie| &movie.watch_history) {
+        if event.watched_on.year() == year {
+            months[event.watched_on.month0() as usize] += 1;
+        }
+    }
+    months
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use super::super::super::movies::{fixtures::collection, WatchEvent};
+
+    fn watched_on(dates: &[(i32, u32, u32)]) -> Vec<WatchEvent> {
+        dates
+            .iter()
+            .map(|(year, month, day)| WatchEvent { watched_on: NaiveDate::from_ymd(*year, *month, *day) })
+            .collect()
+    }
+
+    #[test]
+    fn watch_count_counts_rewatches_and_watched_movies_without_history() {
+        assert_eq!(watch_count(&Movie::default()), 0);
+        assert_eq!(watch_count(&Movie { watch_status: WatchStatus::Watched, ..Movie::default() }), 1);
+        let rewatched_movie = Movie {
+            watch_status: WatchStatus::Watched,
+            watch_history: watched_on(&[(2019, 5, 1), (2020, 1, 2)]),
+            ..Movie::default()
+        };
+        assert_eq!(watch_count(&rewatched_movie), 2);
+    }
+
+    #[test]
+    fn watched_in_year_counts_movies_once() {
+        let (_, movies) = collection(vec![
+            Movie { watch_history: watched_on(&[(2020, 1, 2), (2020, 3, 4)]), ..Movie::default() },
+            Movie { watch_history: watched_on(&[(2019, 12, 31)]), ..Movie::default() },
+            Movie { watch_history: watched_on(&[(2019, 6, 1), (2020, 6, 1)]), ..Movie::default() },
+            Movie { watch_status: WatchStatus::Watched, ..Movie::default() },
+        ]);
+        assert_eq!(watched_in_year(&movies, 2020), 2);
+        assert_eq!(watched_in_year(&movies, 2019), 2);
+        assert_eq!(watched_in_year(&movies, 2018), 0);
+    }
+
+    #[test]
+    fn minutes_watched_counts_rewatches_and_skips_missing_runtime() {
+        let (_, movies) = collection(vec![
+            Movie {
+                runtime: Some(100),
+                watch_status: WatchStatus::Watched,
+                watch_history: watched_on(&[(2019, 5, 1), (2020, 1, 2)]),
+                ..Movie::default()
+            },
+            Movie { runtime: Some(90), watch_status: WatchStatus::Watched, ..Movie::default() },
+            Movie { watch_status: WatchStatus::Watched, watch_history: watched_on(&[(2020, 1, 3)]), ..Movie::default() },
+            Movie { runtime: Some(120), ..Movie::default() },
+        ]);
+        assert_eq!(minutes_watched(&movies), 290);
+    }
+
+    #[test]
+    fn top_genres_sort_ties_by_name() {
+        let genres = |genres: &[&str]| Movie {
+            genres: genres.iter().map(|genre| (*genre).to_owned()).collect(),
+            ..Movie::default()
+        };
+        let (_, movies) = collection(vec![
+            genres(&["Drama", "Sci-Fi"]),
+            genres(&["Comedy", "Sci-Fi"]),
+            genres(&["Action", "Drama", "Western"]),
+        ]);
+        let top_genres = top_genres(&movies, 3);
+        assert_eq!(top_genres, vec![("Drama".to_owned(), 2), ("Sci-Fi".to_owned(), 2), ("Action".to_owned(), 1)]);
+    }
+
+    #[test]
+    fn average_rating_skips_unrated_movies() {
+        assert_eq!(average_rating(&BTreeMap::new()), None);
+        let (_, unrated_movies) = collection(vec![Movie::default(), Movie::default()]);
+        assert_eq!(average_rating(&unrated_movies), None);
+
+        let (_, movies) = collection(vec![
+            Movie { rating: Some(7), ..Movie::default() },
+            Movie::default(),
+            Movie { rating: Some(4), ..Movie::default() },
+        ]);
+        assert_eq!(average_rating(&movies), Some(5.5));
+    }
+
+    #[test]
+    fn watched_per_month_buckets_watches_of_the_year() {
+        let (_, movies) = collection(vec![
+            Movie { watch_history: watched_on(&[(2020, 1, 1), (2020, 1, 31), (2020, 12, 31)]), ..Movie::default() },
+            Movie { watch_history: watched_on(&[(2019, 1, 15), (2020, 2, 29), (2021, 1, 1)]), ..Movie::default() },
+        ]);
+        assert_eq!(watched_per_month(&movies, 2020), [2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+    }
+}
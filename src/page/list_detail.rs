@@ -0,0 +1,515 @@
+use seed::{prelude::*, *};
+
+use seed_style::{px, rem};
+use seed_style::*;
+
+use chrono::prelude::*;
+
+use std::collections::BTreeMap;
+
+use super::lists::{self, List, ListId};
+use super::movies::{self, Movie, MovieId};
+
+const SAVE_LIST_DEBOUNCE_MS: u32 = 800;
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(base_url: Url, list_id: ListId, orders: &mut impl Orders<Msg>) -> Model {
+    orders
+        .perform_cmd(async move { Msg::ListFetched(lists::request_list(list_id).await) })
+        .perform_cmd(async { Msg::MoviesFetched(movies::request_movies().await) });
+
+    Model {
+        base_url,
+        list_id,
+        changes_status: ChangesStatus::NoChanges,
+        errors: Vec::new(),
+
+        list: RemoteData::Loading,
+        movies: RemoteData::Loading,
+        movie_to_add: None,
+        drag: None,
+        save_list_handle: None,
+        saving: false,
+        unsaved_changes: false,
+    }
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    base_url: Url,
+    list_id: ListId,
+    changes_status: ChangesStatus,
+    errors: Vec<FetchError>,
+
+    list: RemoteData<List>,
+    movies: RemoteData<BTreeMap<MovieId, Movie>>,
+    movie_to_add: Option<MovieId>,
+    drag: Option<Drag>,
+    save_list_handle: Option<CmdHandle>,
+    /// A save is in flight; the next one waits, so an older list can't overwrite a newer one on the server.
+    saving: bool,
+    unsaved_changes: bool,
+}
+
+enum RemoteData<T> {
+    NotAsked,
+    Loading,
+    Loaded(T),
+}
+
+enum ChangesStatus {
+    NoChanges,
+    Saving { requests_in_flight: usize },
+    Saved(DateTime<Local>),
+}
+
+/// Indices into `List::movie_ids`.
+struct Drag {
+    from: usize,
+    to: usize,
+}
+
+/// Moves the entry at `from` to `to`; the entries in between shift by one.
+fn move_entry<T>(entries: &mut Vec<T>, from: usize, to: usize) -> bool {
+    if from == to || from >= entries.len() || to >= entries.len() {
+        return false;
+    }
+    let entry = entries.remove(from);
+    entries.insert(to, entry);
+    true
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    ListFetched(fetch::Result<List>),
+    MoviesFetched(fetch::Result<BTreeMap<MovieId, Movie>>),
+    SaveList,
+    ChangesSaved(Option<FetchError>),
+    ClearErrors,
+
+    MovieToAddChanged(String),
+    AddMovie,
+    RemoveMovie(usize),
+    MoveMovie { from: usize, to: usize },
+
+    DragStarted(usize),
+    DraggedOver(usize),
+    Dropped,
+    DragEnded,
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::ListFetched(Ok(list)) => {
+            // Local changes contain the whole list and overwrite the server one when they are saved.
+            if not(model.unsaved_changes || model.saving) {
+                model.list = RemoteData::Loaded(list);
+            }
+        },
+        Msg::ListFetched(Err(fetch_error)) => {
+            model.list = RemoteData::NotAsked;
+            model.errors.push(fetch_error);
+        },
+        Msg::MoviesFetched(Ok(movies)) => model.movies = RemoteData::Loaded(movies),
+        Msg::MoviesFetched(Err(fetch_error)) => {
+            model.movies = RemoteData::NotAsked;
+            model.errors.push(fetch_error);
+        },
+
+        Msg::SaveList => send_list(model, orders),
+        Msg::ChangesSaved(None) => {
+            model.changes_status = request_finished(&model.changes_status);
+            model.saving = false;
+            // Send changes made while the request was in flight.
+            if model.save_list_handle.is_none() {
+                send_list(model, orders);
+            }
+        },
+        Msg::ChangesSaved(Some(fetch_error)) => {
+            model.changes_status = request_finished(&model.changes_status);
+            model.saving = false;
+            model.errors.push(fetch_error);
+            // Show the list as the server has it, unless it's been changed since.
+            let list_id = model.list_id;
+            orders.perform_cmd(async move { Msg::ListFetched(lists::request_list(list_id).await) });
+            if model.save_list_handle.is_none() {
+                send_list(model, orders);
+            }
+        },
+
+        Msg::ClearErrors => model.errors.clear(),
+
+        // ------ Entries ------
+
+        Msg::MovieToAddChanged(movie_id) => model.movie_to_add = MovieId::from_string(&movie_id).ok(),
+        Msg::AddMovie => {
+            let (list, movie_id) = match (&mut model.list, model.movie_to_add.take()) {
+                (RemoteData::Loaded(list), Some(movie_id)) => (list, movie_id),
+                _ => return,
+            };
+            if list.movie_ids.contains(&movie_id) {
+                return;
+            }
+            list.movie_ids.push(movie_id);
+            save_list(model, orders);
+        },
+        Msg::RemoveMovie(index) => {
+            if let RemoteData::Loaded(list) = &mut model.list {
+                if index < list.movie_ids.len() {
+                    list.movie_ids.remove(index);
+                    save_list(model, orders);
+                }
+            }
+        },
+        Msg::MoveMovie { from, to } => {
+            if let RemoteData::Loaded(list) = &mut model.list {
+                if move_entry(&mut list.movie_ids, from, to) {
+                    save_list(model, orders);
+                }
+            }
+        },
+
+        // ------ Drag and drop ------
+
+        Msg::DragStarted(index) => model.drag = Some(Drag { from: index, to: index }),
+        Msg::DraggedOver(index) => match &mut model.drag {
+            // `dragover` fires every few hundred milliseconds.
+            Some(drag) if drag.to != index => drag.to = index,
+            _ => {
+                orders.skip();
+            },
+        },
+        Msg::Dropped => {
+            if let Some(Drag { from, to }) = model.drag.take() {
+                orders.send_msg(Msg::MoveMovie { from, to });
+            }
+        },
+        Msg::DragEnded => model.drag = None,
+    }
+}
+
+/// Saves the list once the changes stop for a moment, e.g. after a few quick moves.
+fn save_list(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    model.unsaved_changes = true;
+    // Replacing the handle aborts the previous timeout.
+    model.save_list_handle = Some(orders.perform_cmd_with_handle(
+        cmds::timeout(SAVE_LIST_DEBOUNCE_MS, || Msg::SaveList)
+    ));
+}
+
+fn send_list(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    model.save_list_handle = None;
+    if model.saving || not(model.unsaved_changes) {
+        return;
+    }
+    let list = match &model.list {
+        RemoteData::Loaded(list) => list.clone(),
+        _ => return,
+    };
+    model.unsaved_changes = false;
+    model.saving = true;
+    let list_id = model.list_id;
+    model.changes_status = request_started(&model.changes_status);
+    orders.perform_cmd(async move { Msg::ChangesSaved(lists::patch_list(list_id, list).await.err()) });
+}
+
+fn request_started(changes_status: &ChangesStatus) -> ChangesStatus {
+    match changes_status {
+        ChangesStatus::Saving { requests_in_flight } => {
+            ChangesStatus::Saving { requests_in_flight: requests_in_flight + 1 }
+        },
+        ChangesStatus::NoChanges | ChangesStatus::Saved(_) => {
+            ChangesStatus::Saving { requests_in_flight: 1 }
+        },
+    }
+}
+
+fn request_finished(changes_status: &ChangesStatus) -> ChangesStatus {
+    match changes_status {
+        ChangesStatus::Saving { requests_in_flight } if *requests_in_flight > 1 => {
+            ChangesStatus::Saving { requests_in_flight: requests_in_flight - 1 }
+        },
+        _ => ChangesStatus::Saved(Local::now()),
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        s()
+            .py(rem(1.5))
+            .px(rem(0.75)),
+        a![
+            attrs!{At::Href => crate::Urls::new(&model.base_url).lists()},
+            "← Lists",
+        ],
+        view_changes_status(&model.changes_status),
+        view_errors(&model.errors),
+        match (&model.list, &model.movies) {
+            (RemoteData::Loaded(list), RemoteData::Loaded(movies)) => div![
+                h1![
+                    s()
+                        .font_size(rem(2))
+                        .font_weight("600")
+                        .my(rem(1)),
+                    &list.name,
+                ],
+                view_add_movie(list, movies, model.movie_to_add),
+                view_entries(list, movies, model.drag.as_ref(), &model.base_url),
+            ],
+            (RemoteData::NotAsked, _) | (_, RemoteData::NotAsked) => empty![],
+            _ => view_message("Loading list..."),
+        }
+    ]
+}
+
+fn view_changes_status(changes_status: &ChangesStatus) -> Node<Msg> {
+    let text = match changes_status {
+        ChangesStatus::NoChanges => return empty![],
+        ChangesStatus::Saving { .. } => "Saving…".to_owned(),
+        ChangesStatus::Saved(date_time) => format!("Saved at {}", date_time.format("%H:%M")),
+    };
+    p![
+        s()
+            .color("#7a7a7a")
+            .font_size(rem(0.875))
+            .mb(rem(0.75))
+            .text_align(CssTextAlign::Right),
+        text
+    ]
+}
+
+fn view_errors(errors: &[FetchError]) -> Node<Msg> {
+    if errors.is_empty() {
+        return empty![];
+    }
+    div![
+        C!["notification"],
+        s()
+            .bg_color("#feecf0")
+            .color("#cc0f35")
+            .border_radius(px(4))
+            .mb(rem(1.5))
+            .py(rem(1.25))
+            .px(rem(1.5)),
+        ul![
+            errors.iter().map(|error| li![format!("{:?}", error)])
+        ],
+        button![
+            C!["button"],
+            s()
+                .mt(rem(0.75))
+                .cursor(CssCursor::Pointer),
+            "Clear errors",
+            ev(Ev::Click, |_| Msg::ClearErrors),
+        ]
+    ]
+}
+
+fn view_message(message: &str) -> Node<Msg> {
+    p![
+        s()
+            .color("#7a7a7a")
+            .text_align(CssTextAlign::Center)
+            .py(rem(3)),
+        message
+    ]
+}
+
+fn view_add_movie(list: &List, movies: &BTreeMap<MovieId, Movie>, movie_to_add: Option<MovieId>) -> Node<Msg> {
+    let mut candidates = movies
+        .iter()
+        .filter(|(movie_id, _)| not(list.movie_ids.contains(movie_id)))
+        .collect::<Vec<_>>();
+    candidates.sort_by_cached_key(|(_, movie)| movie.title.to_lowercase());
+    form![
+        s()
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .mb(rem(1)),
+        ev(Ev::Submit, |event| {
+            event.prevent_default();
+            Msg::AddMovie
+        }),
+        select![
+            s()
+                .flex_grow("1")
+                .py(rem(0.25)),
+            option![
+                attrs!{
+                    At::Value => "",
+                    At::Selected => movie_to_add.is_none().as_at_value(),
+                },
+                "— add a movie —",
+            ],
+            candidates.into_iter().map(|(movie_id, movie)| option![
+                attrs!{
+                    At::Value => movie_id,
+                    At::Selected => (movie_to_add == Some(*movie_id)).as_at_value(),
+                },
+                view_title(movie),
+            ]),
+            input_ev(Ev::Change, Msg::MovieToAddChanged),
+        ],
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .ml(rem(0.5)),
+            attrs!{
+                At::Type => "submit",
+                At::Disabled => movie_to_add.is_none().as_at_value(),
+            },
+            "Add",
+        ],
+    ]
+}
+
+fn view_entries(list: &List, movies: &BTreeMap<MovieId, Movie>, drag: Option<&Drag>, base_url: &Url) -> Node<Msg> {
+    if list.movie_ids.is_empty() {
+        return view_message("The list is empty.");
+    }
+    let last_index = list.movie_ids.len() - 1;
+    let s_button = || {
+        s()
+            .cursor(CssCursor::Pointer)
+            .ml(rem(0.5))
+    };
+    ol![
+        list.movie_ids.iter().enumerate().map(|(index, movie_id)| {
+            let dragged = drag.map_or(false, |drag| drag.from == index);
+            let drop_target = drag.map_or(false, |drag| drag.to == index && drag.from != index);
+            li![
+                s()
+                    .align_items(CssAlignItems::Center)
+                    .border_bottom("1px solid #ededed")
+                    .raw("cursor: move;")
+                    .display(CssDisplay::Flex)
+                    .py(rem(0.5)),
+                if dragged {
+                    s().opacity("0.4")
+                } else {
+                    s()
+                },
+                // The dragged movie takes the place of the drop target.
+                match drag {
+                    Some(drag) if drop_target && drag.from < index => s().border_bottom("3px solid #3273dc"),
+                    Some(_) if drop_target => s().border_top("3px solid #3273dc"),
+                    _ => s(),
+                },
+                attrs!{At::Draggable => "true"},
+                drag_ev(Ev::DragStart, move |event| {
+                    // Firefox doesn't start dragging without data.
+                    if let Some(data_transfer) = event.data_transfer() {
+                        data_transfer.set_effect_allowed("move");
+                        let _ = data_transfer.set_data("text/plain", "");
+                    }
+                    Msg::DragStarted(index)
+                }),
+                drag_ev(Ev::DragOver, move |event| {
+                    // Allows dropping.
+                    event.prevent_default();
+                    Msg::DraggedOver(index)
+                }),
+                drag_ev(Ev::Drop, |event| {
+                    event.prevent_default();
+                    Msg::Dropped
+                }),
+                ev(Ev::DragEnd, |_| Msg::DragEnded),
+                span![
+                    s()
+                        .color("#b5b5b5")
+                        .mr(rem(0.75)),
+                    attrs!{At::AriaHidden => "true"},
+                    "☰",
+                ],
+                match movies.get(movie_id) {
+                    Some(movie) => a![
+                        s()
+                            .flex_grow("1"),
+                        attrs!{At::Href => crate::Urls::new(base_url).movie(*movie_id)},
+                        view_title(movie),
+                    ],
+                    // The movie has been deleted after the list has been loaded.
+                    None => em![
+                        s()
+                            .color("#7a7a7a")
+                            .flex_grow("1"),
+                        "Deleted movie",
+                    ],
+                },
+                button![
+                    C!["button"],
+                    s_button(),
+                    attrs!{
+                        At::Disabled => (index == 0).as_at_value(),
+                        At::Title => "Move up",
+                    },
+                    "↑",
+                    ev(Ev::Click, move |_| Msg::MoveMovie { from: index, to: index.saturating_sub(1) }),
+                ],
+                button![
+                    C!["button"],
+                    s_button(),
+                    attrs!{
+                        At::Disabled => (index == last_index).as_at_value(),
+                        At::Title => "Move down",
+                    },
+                    "↓",
+                    ev(Ev::Click, move |_| Msg::MoveMovie { from: index, to: index + 1 }),
+                ],
+                button![
+                    C!["button"],
+                    s_button(),
+                    "Remove",
+                    ev(Ev::Click, move |_| Msg::RemoveMovie(index)),
+                ],
+            ]
+        })
+    ]
+}
+
+fn view_title(movie: &Movie) -> String {
+    match movie.year {
+        Some(year) => format!("{} ({})", movie.title, year),
+        None => movie.title.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_entry_shifts_the_entries_in_between() {
+        let mut entries = vec!['a', 'b', 'c', 'd'];
+        assert!(move_entry(&mut entries, 0, 2));
+        assert_eq!(entries, vec!['b', 'c', 'a', 'd']);
+        assert!(move_entry(&mut entries, 3, 0));
+        assert_eq!(entries, vec!['d', 'b', 'c', 'a']);
+        assert!(move_entry(&mut entries, 1, 2));
+        assert_eq!(entries, vec!['d', 'c', 'b', 'a']);
+    }
+
+    #[test]
+    fn move_entry_ignores_invalid_moves() {
+        let mut entries = vec!['a', 'b', 'c'];
+        assert!(!move_entry(&mut entries, 1, 1));
+        assert!(!move_entry(&mut entries, 3, 0));
+        assert!(!move_entry(&mut entries, 0, 3));
+        assert!(!move_entry(&mut Vec::<char>::new(), 0, 0));
+        assert_eq!(entries, vec!['a', 'b', 'c']);
+    }
+}
@@ -0,0 +1,548 @@
+use seed::{prelude::*, *};
+
+use seed_style::{pc, px, rem};
+use seed_style::*;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use std::collections::BTreeMap;
+
+use super::movies::MovieId;
+
+pub type ListId = Ulid;
+
+const LISTS: &str = "lists";
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(base_url: Url, orders: &mut impl Orders<Msg>) -> Model {
+    orders.perform_cmd(async { Msg::ListsFetched(request_lists().await) });
+
+    Model {
+        base_url,
+        changes_status: ChangesStatus::NoChanges,
+        errors: Vec::new(),
+
+        lists: RemoteData::Loading,
+        new_list_name: String::new(),
+        rename: None,
+        changes_in_flight: BTreeMap::new(),
+        next_change_id: 0,
+    }
+}
+
+pub async fn request_lists() -> fetch::Result<BTreeMap<ListId, List>> {
+    fetch(format!("{}/{}", crate::API_URL, LISTS))
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
+pub async fn request_list(list_id: ListId) -> fetch::Result<List> {
+    fetch(format!("{}/{}/{}", crate::API_URL, LISTS, list_id))
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
+async fn post_list(list_id: ListId, list: List) -> fetch::Result<()> {
+    Request::new(format!("{}/{}", crate::API_URL, LISTS))
+        .method(Method::Post)
+        .json(&NewList { id: list_id, list: &list })?
+        .fetch()
+        .await?
+        .check_status()
+        .map(drop)
+}
+
+/// Saves the name and the order of movies.
+pub async fn patch_list(list_id: ListId, list: List) -> fetch::Result<()> {
+    Request::new(format!("{}/{}/{}", crate::API_URL, LISTS, list_id))
+        .method(Method::Patch)
+        .json(&list)?
+        .fetch()
+        .await?
+        .check_status()
+        .map(drop)
+}
+
+async fn patch_list_name(list_id: ListId, name: String) -> fetch::Result<()> {
+    Request::new(format!("{}/{}/{}", crate::API_URL, LISTS, list_id))
+        .method(Method::Patch)
+        .json(&ListName { name: &name })?
+        .fetch()
+        .await?
+        .check_status()
+        .map(drop)
+}
+
+/// Appends the movies to the end of the list; the server skips movies already in the list.
+pub async fn post_list_movies(list_id: ListId, movie_ids: Vec<MovieId>) -> fetch::Result<()> {
+    Request::new(format!("{}/{}/{}/movies", crate::API_URL, LISTS, list_id))
+        .method(Method::Post)
+        .json(&movie_ids)?
+        .fetch()
+        .await?
+        .check_status()
+        .map(drop)
+}
+
+async fn delete_list(list_id: ListId) -> fetch::Result<()> {
+    Request::new(format!("{}/{}/{}", crate::API_URL, LISTS, list_id))
+        .method(Method::Delete)
+        .fetch()
+        .await?
+        .check_status()
+        .map(drop)
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    base_url: Url,
+    changes_status: ChangesStatus,
+    errors: Vec<FetchError>,
+
+    lists: RemoteData<BTreeMap<ListId, List>>,
+    new_list_name: String,
+    rename: Option<Rename>,
+    /// Optimistic changes waiting for the server, in the order they were sent.
+    /// They are applied again to refetched lists, so they don't disappear before they are saved.
+    changes_in_flight: BTreeMap<ChangeId, Change>,
+    next_change_id: ChangeId,
+}
+
+enum RemoteData<T> {
+    NotAsked,
+    Loading,
+    Loaded(T),
+}
+
+enum ChangesStatus {
+    NoChanges,
+    Saving { requests_in_flight: usize },
+    Saved(DateTime<Local>),
+}
+
+/// A named collection of movies, e.g. "Halloween marathon".
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct List {
+    pub name: String,
+    /// In the order chosen by the user.
+    #[serde(default)]
+    pub movie_ids: Vec<MovieId>,
+}
+
+#[derive(Serialize)]
+struct NewList<'a> {
+    id: ListId,
+    #[serde(flatten)]
+    list: &'a List,
+}
+
+/// Renames the list without sending `movie_ids`, so it can't revert movies added meanwhile, e.g. on other pages.
+#[derive(Serialize)]
+struct ListName<'a> {
+    name: &'a str,
+}
+
+struct Rename {
+    list_id: ListId,
+    name: String,
+}
+
+type ChangeId = u64;
+
+#[derive(Clone)]
+enum Change {
+    Create(ListId, List),
+    Rename(ListId, String),
+    Delete(ListId),
+}
+
+impl Change {
+    async fn send(self) -> fetch::Result<()> {
+        match self {
+            Self::Create(list_id, list) => post_list(list_id, list).await,
+            Self::Rename(list_id, name) => patch_list_name(list_id, name).await,
+            Self::Delete(list_id) => delete_list(list_id).await,
+        }
+    }
+
+    /// Makes the same change in `lists` - e.g. in fresh lists from the server.
+    fn apply(&self, lists: &mut BTreeMap<ListId, List>) {
+        match self {
+            Self::Create(list_id, list) => {
+                lists.insert(*list_id, list.clone());
+            },
+            Self::Rename(list_id, name) => {
+                if let Some(list) = lists.get_mut(list_id) {
+                    list.name = name.clone();
+                }
+            },
+            Self::Delete(list_id) => {
+                lists.remove(list_id);
+            },
+        }
+    }
+}
+
+/// Lists sorted by name.
+pub fn sorted_lists(lists: &BTreeMap<ListId, List>) -> Vec<(&ListId, &List)> {
+    let mut lists = lists.iter().collect::<Vec<_>>();
+    lists.sort_by_cached_key(|(_, list)| list.name.to_lowercase());
+    lists
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    ListsFetched(fetch::Result<BTreeMap<ListId, List>>),
+    ChangesSaved(ChangeId, Option<FetchError>),
+    ClearErrors,
+
+    NewListNameChanged(String),
+    CreateList,
+
+    StartRename(ListId),
+    RenameChanged(String),
+    SaveRename,
+    CancelRename,
+
+    DeleteList(ListId),
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::ListsFetched(Ok(mut lists)) => {
+            for change in model.changes_in_flight.values() {
+                change.apply(&mut lists);
+            }
+            model.lists = RemoteData::Loaded(lists);
+        },
+        Msg::ListsFetched(Err(fetch_error)) => {
+            model.lists = RemoteData::NotAsked;
+            model.errors.push(fetch_error);
+        },
+
+        Msg::ChangesSaved(change_id, None) => {
+            model.changes_status = request_finished(&model.changes_status);
+            model.changes_in_flight.remove(&change_id);
+        },
+        Msg::ChangesSaved(change_id, Some(fetch_error)) => {
+            model.changes_status = request_finished(&model.changes_status);
+            model.changes_in_flight.remove(&change_id);
+            model.errors.push(fetch_error);
+            // Show the lists as the server has them.
+            orders.perform_cmd(async { Msg::ListsFetched(request_lists().await) });
+        },
+
+        Msg::ClearErrors => model.errors.clear(),
+
+        // ------ NewList ------
+
+        Msg::NewListNameChanged(name) => model.new_list_name = name,
+        Msg::CreateList => {
+            let lists = match &mut model.lists {
+                RemoteData::Loaded(lists) => lists,
+                _ => return,
+            };
+            let name = model.new_list_name.trim();
+            if name.is_empty() {
+                return;
+            }
+            let list_id = ListId::new();
+            let list = List { name: name.to_owned(), movie_ids: Vec::new() };
+            lists.insert(list_id, list.clone());
+            model.new_list_name.clear();
+            send_change(model, Change::Create(list_id, list), orders);
+        },
+
+        // ------ Rename ------
+
+        Msg::StartRename(list_id) => {
+            if let RemoteData::Loaded(lists) = &model.lists {
+                if let Some(list) = lists.get(&list_id) {
+                    model.rename = Some(Rename { list_id, name: list.name.clone() });
+                }
+            }
+        },
+        Msg::RenameChanged(name) => {
+            if let Some(rename) = &mut model.rename {
+                rename.name = name;
+            }
+        },
+        Msg::SaveRename => {
+            let (lists, rename) = match (&mut model.lists, model.rename.take()) {
+                (RemoteData::Loaded(lists), Some(rename)) => (lists, rename),
+                _ => return,
+            };
+            let name = rename.name.trim();
+            let list = match lists.get_mut(&rename.list_id) {
+                Some(list) if not(name.is_empty()) && list.name != name => list,
+                _ => return,
+            };
+            list.name = name.to_owned();
+            let change = Change::Rename(rename.list_id, list.name.clone());
+            send_change(model, change, orders);
+        },
+        Msg::CancelRename => model.rename = None,
+
+        // ------ Delete ------
+
+        Msg::DeleteList(list_id) => {
+            let lists = match &mut model.lists {
+                RemoteData::Loaded(lists) => lists,
+                _ => return,
+            };
+            let question = match lists.get(&list_id) {
+                Some(list) => format!("Delete the list \"{}\"? Its movies stay in your collection.", list.name),
+                None => return,
+            };
+            if not(window().confirm_with_message(&question).unwrap_or_default()) {
+                return;
+            }
+            lists.remove(&list_id);
+            send_change(model, Change::Delete(list_id), orders);
+        },
+    }
+}
+
+fn send_change(model: &mut Model, change: Change, orders: &mut impl Orders<Msg>) {
+    let change_id = model.next_change_id;
+    model.next_change_id += 1;
+    model.changes_in_flight.insert(change_id, change.clone());
+    model.changes_status = request_started(&model.changes_status);
+    orders.perform_cmd(async move { Msg::ChangesSaved(change_id, change.send().await.err()) });
+}
+
+fn request_started(changes_status: &ChangesStatus) -> ChangesStatus {
+    match changes_status {
+        ChangesStatus::Saving { requests_in_flight } => {
+            ChangesStatus::Saving { requests_in_flight: requests_in_flight + 1 }
+        },
+        ChangesStatus::NoChanges | ChangesStatus::Saved(_) => {
+            ChangesStatus::Saving { requests_in_flight: 1 }
+        },
+    }
+}
+
+fn request_finished(changes_status: &ChangesStatus) -> ChangesStatus {
+    match changes_status {
+        ChangesStatus::Saving { requests_in_flight } if *requests_in_flight > 1 => {
+            ChangesStatus::Saving { requests_in_flight: requests_in_flight - 1 }
+        },
+        _ => ChangesStatus::Saved(Local::now()),
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        s()
+            .py(rem(1.5))
+            .px(rem(0.75)),
+        view_changes_status(&model.changes_status),
+        view_errors(&model.errors),
+        view_new_list_form(&model.new_list_name),
+        match &model.lists {
+            RemoteData::NotAsked => empty![],
+            RemoteData::Loading => view_message("Loading lists..."),
+            RemoteData::Loaded(lists) if lists.is_empty() => view_message("No lists yet."),
+            RemoteData::Loaded(lists) => ul![
+                sorted_lists(lists)
+                    .into_iter()
+                    .map(|(list_id, list)| view_list(*list_id, list, model.rename.as_ref(), &model.base_url))
+            ],
+        }
+    ]
+}
+
+fn view_changes_status(changes_status: &ChangesStatus) -> Node<Msg> {
+    let text = match changes_status {
+        ChangesStatus::NoChanges => return empty![],
+        ChangesStatus::Saving { .. } => "Saving…".to_owned(),
+        ChangesStatus::Saved(date_time) => format!("Saved at {}", date_time.format("%H:%M")),
+    };
+    p![
+        s()
+            .color("#7a7a7a")
+            .font_size(rem(0.875))
+            .mb(rem(0.75))
+            .text_align(CssTextAlign::Right),
+        text
+    ]
+}
+
+fn view_errors(errors: &[FetchError]) -> Node<Msg> {
+    if errors.is_empty() {
+        return empty![];
+    }
+    div![
+        C!["notification"],
+        s()
+            .bg_color("#feecf0")
+            .color("#cc0f35")
+            .border_radius(px(4))
+            .mb(rem(1.5))
+            .py(rem(1.25))
+            .px(rem(1.5)),
+        ul![
+            errors.iter().map(|error| li![format!("{:?}", error)])
+        ],
+        button![
+            C!["button"],
+            s()
+                .mt(rem(0.75))
+                .cursor(CssCursor::Pointer),
+            "Clear errors",
+            ev(Ev::Click, |_| Msg::ClearErrors),
+        ]
+    ]
+}
+
+fn view_message(message: &str) -> Node<Msg> {
+    p![
+        s()
+            .color("#7a7a7a")
+            .text_align(CssTextAlign::Center)
+            .py(rem(3)),
+        message
+    ]
+}
+
+fn view_new_list_form(name: &str) -> Node<Msg> {
+    form![
+        s()
+            .align_items(CssAlignItems::Center)
+            .border("1px solid #ededed")
+            .border_radius(px(4))
+            .display(CssDisplay::Flex)
+            .mb(rem(1.5))
+            .p(rem(1)),
+        ev(Ev::Submit, |event| {
+            event.prevent_default();
+            Msg::CreateList
+        }),
+        view_name_input(name, "New list, e.g. Halloween marathon", Msg::NewListNameChanged),
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .ml(rem(0.5)),
+            attrs!{
+                At::Type => "submit",
+                At::Disabled => name.trim().is_empty().as_at_value(),
+            },
+            "Create list",
+        ]
+    ]
+}
+
+fn view_name_input(
+    name: &str,
+    placeholder: &str,
+    on_input: impl FnOnce(String) -> Msg + Clone + 'static,
+) -> Node<Msg> {
+    input![
+        s()
+            .border("1px solid #dbdbdb")
+            .border_radius(px(4))
+            .flex_grow("1")
+            .px(rem(0.5))
+            .py(rem(0.25))
+            .w(pc(100)),
+        attrs!{
+            At::Value => name,
+            At::Placeholder => placeholder,
+        },
+        input_ev(Ev::Input, on_input),
+    ]
+}
+
+fn view_list(list_id: ListId, list: &List, rename: Option<&Rename>, base_url: &Url) -> Node<Msg> {
+    let s_button = || {
+        s()
+            .cursor(CssCursor::Pointer)
+            .ml(rem(0.5))
+    };
+    li![
+        s()
+            .align_items(CssAlignItems::Center)
+            .border_bottom("1px solid #ededed")
+            .display(CssDisplay::Flex)
+            .py(rem(0.75)),
+        match rename {
+            Some(rename) if rename.list_id == list_id => form![
+                s()
+                    .display(CssDisplay::Flex)
+                    .flex_grow("1"),
+                ev(Ev::Submit, |event| {
+                    event.prevent_default();
+                    Msg::SaveRename
+                }),
+                view_name_input(&rename.name, "List name", Msg::RenameChanged),
+                button![
+                    C!["button"],
+                    s_button(),
+                    attrs!{At::Type => "submit"},
+                    "Save",
+                ],
+                button![
+                    C!["button"],
+                    s_button(),
+                    attrs!{At::Type => "button"},
+                    "Cancel",
+                    ev(Ev::Click, |_| Msg::CancelRename),
+                ],
+            ],
+            _ => div![
+                s()
+                    .align_items(CssAlignItems::Center)
+                    .display(CssDisplay::Flex)
+                    .flex_grow("1"),
+                a![
+                    s()
+                        .font_size(rem(1.25))
+                        .font_weight("600")
+                        .flex_grow("1"),
+                    attrs!{At::Href => crate::Urls::new(base_url).list(list_id)},
+                    &list.name,
+                ],
+                span![
+                    s()
+                        .color("#7a7a7a"),
+                    match list.movie_ids.len() {
+                        1 => "1 movie".to_owned(),
+                        count => format!("{} movies", count),
+                    },
+                ],
+                button![
+                    C!["button"],
+                    s_button(),
+                    "Rename",
+                    ev(Ev::Click, move |_| Msg::StartRename(list_id)),
+                ],
+                button![
+                    C!["button"],
+                    s_button(),
+                    "Delete",
+                    ev(Ev::Click, move |_| Msg::DeleteList(list_id)),
+                ],
+            ],
+        }
+    ]
+}
@@ -0,0 +1,773 @@
+use seed::{prelude::*, *};
+
+use seed_style::{pc, px, rem};
+use seed_style::*;
+
+use chrono::prelude::*;
+
+use std::collections::BTreeMap;
+
+use super::movies::{self, conflict, recommendations, Filters, Movie, MovieField, MovieId, Review};
+
+mod poster_upload;
+
+const SIMILAR_MOVIES: usize = 6;
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(base_url: Url, movie_id: MovieId, orders: &mut impl Orders<Msg>) -> Model {
+    orders.perform_cmd(async move { Msg::MovieFetched(movies::request_movie(movie_id).await) });
+    orders.perform_cmd(async { Msg::MoviesFetched(movies::request_movies().await) });
+
+    Model {
+        base_url,
+        movie_id,
+        changes_status: ChangesStatus::NoChanges,
+        errors: Vec::new(),
+
+        movie: RemoteData::Loading,
+        saved_movie: None,
+        movies: RemoteData::Loading,
+        similar_movies: Vec::new(),
+        form: None,
+        spoilers_visible: false,
+        conflict: None,
+        poster_upload: None,
+        saving: false,
+        save_queued: false,
+        queued_poster: None,
+    }
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    base_url: Url,
+    movie_id: MovieId,
+    changes_status: ChangesStatus,
+    errors: Vec<FetchError>,
+
+    movie: RemoteData<Movie>,
+    /// The movie as the server has it; it replaces `movie` when a save fails.
+    saved_movie: Option<Movie>,
+    /// The whole collection for "More like this".
+    movies: RemoteData<BTreeMap<MovieId, Movie>>,
+    /// "More like this", updated when the movie or the collection changes.
+    similar_movies: Vec<(MovieId, f64)>,
+    form: Option<Form>,
+    spoilers_visible: bool,
+    conflict: Option<conflict::Model>,
+    poster_upload: Option<poster_upload::Model>,
+    /// A save or a poster upload is in flight; the next one has to wait for the new version,
+    /// otherwise the server would refuse it.
+    saving: bool,
+    /// `movie` has changed since the save in flight has been sent.
+    save_queued: bool,
+    /// The poster and its thumbnail waiting for the save in flight.
+    queued_poster: Option<(web_sys::Blob, web_sys::Blob)>,
+}
+
+enum RemoteData<T> {
+    NotAsked,
+    Loading,
+    Loaded(T),
+}
+
+enum ChangesStatus {
+    NoChanges,
+    Saving { requests_in_flight: usize },
+    Saved(DateTime<Local>),
+}
+
+#[derive(Default)]
+struct Form {
+    texts: BTreeMap<MovieField, String>,
+    review_spoiler: bool,
+    errors: BTreeMap<MovieField, String>,
+}
+
+impl Form {
+    fn from_movie(movie: &Movie) -> Self {
+        Self {
+            texts: MovieField::ALL.iter().map(|field| (*field, field.text(movie))).collect(),
+            review_spoiler: movie.review.as_ref().map_or(false, |review| review.spoiler),
+            errors: BTreeMap::new(),
+        }
+    }
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    MovieFetched(fetch::Result<Movie>),
+    MoviesFetched(fetch::Result<BTreeMap<MovieId, Movie>>),
+    /// Contains the sent movie.
+    ChangesSaved(Movie, fetch::Result<u64>),
+    ServerMovieFetched(fetch::Result<Movie>),
+    ConflictMsg(conflict::Msg),
+    ClearErrors,
+
+    StartEdit,
+    FieldChanged(MovieField, String),
+    ReviewSpoilerToggled,
+    CancelEdit,
+    Save,
+
+    ShowSpoilers,
+
+    OpenPosterUpload,
+    PosterUploadMsg(poster_upload::Msg),
+    PosterUploaded(fetch::Result<movies::SavedPoster>),
+
+    Delete,
+    Deleted(Option<FetchError>),
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::MovieFetched(Ok(movie)) => {
+            model.saved_movie = Some(movie.clone());
+            model.movie = RemoteData::Loaded(movie);
+            update_similar_movies(model);
+        },
+        Msg::MovieFetched(Err(fetch_error)) => {
+            model.movie = RemoteData::NotAsked;
+            model.errors.push(fetch_error);
+        },
+        Msg::MoviesFetched(Ok(movies)) => {
+            model.movies = RemoteData::Loaded(movies);
+            update_similar_movies(model);
+        },
+        Msg::MoviesFetched(Err(fetch_error)) => {
+            model.movies = RemoteData::NotAsked;
+            model.errors.push(fetch_error);
+        },
+
+        Msg::ChangesSaved(mut sent_movie, Ok(version)) => {
+            model.changes_status = request_finished(&model.changes_status);
+            model.saving = false;
+            sent_movie.version = version;
+            model.saved_movie = Some(sent_movie);
+            if let RemoteData::Loaded(movie) = &mut model.movie {
+                movie.version = version;
+            }
+            send_queued_changes(model, orders);
+        },
+        Msg::ChangesSaved(_, Err(fetch_error)) => {
+            model.changes_status = request_finished(&model.changes_status);
+            model.saving = false;
+            if movies::is_conflict(&fetch_error) {
+                // The conflict dialog contains the queued changes.
+                model.save_queued = false;
+                let movie_id = model.movie_id;
+                orders.perform_cmd(async move { Msg::ServerMovieFetched(movies::request_movie(movie_id).await) });
+            } else {
+                model.errors.push(fetch_error);
+                // The queued changes are based on the refused ones.
+                model.save_queued = false;
+                if let Some(saved_movie) = &model.saved_movie {
+                    model.movie = RemoteData::Loaded(saved_movie.clone());
+                    update_similar_movies(model);
+                }
+            }
+            send_queued_changes(model, orders);
+        },
+
+        // ------ Conflict ------
+
+        Msg::ServerMovieFetched(Ok(theirs)) => {
+            model.saved_movie = Some(theirs.clone());
+            if let RemoteData::Loaded(mine) = &model.movie {
+                model.conflict = Some(conflict::Model::new(model.movie_id, mine.clone(), theirs));
+            }
+        },
+        Msg::ServerMovieFetched(Err(fetch_error)) => model.errors.push(fetch_error),
+        Msg::ConflictMsg(conflict::Msg::KeepMine) => {
+            if let Some(conflict) = model.conflict.take() {
+                save_movie(model, conflict.kept_movie(), orders);
+            }
+        },
+        Msg::ConflictMsg(conflict::Msg::TakeTheirs) => {
+            if let Some(conflict) = model.conflict.take() {
+                model.movie = RemoteData::Loaded(conflict.theirs().clone());
+                update_similar_movies(model);
+            }
+        },
+        Msg::ConflictMsg(conflict::Msg::SaveMerged) => {
+            if let Some(conflict) = model.conflict.take() {
+                save_movie(model, conflict.resolved_movie(), orders);
+            }
+        },
+        Msg::ConflictMsg(msg) => {
+            if let Some(conflict) = &mut model.conflict {
+                conflict::update(msg, conflict, &mut orders.proxy(Msg::ConflictMsg));
+            }
+        },
+
+        Msg::ClearErrors => model.errors.clear(),
+
+        // ------ Form ------
+
+        Msg::StartEdit => {
+            if let RemoteData::Loaded(movie) = &model.movie {
+                model.form = Some(Form::from_movie(movie));
+            }
+        },
+        Msg::FieldChanged(field, text) => {
+            if let Some(form) = &mut model.form {
+                form.texts.insert(field, text);
+            }
+        },
+        Msg::ReviewSpoilerToggled => {
+            if let Some(form) = &mut model.form {
+                form.review_spoiler = not(form.review_spoiler);
+            }
+        },
+        Msg::CancelEdit => model.form = None,
+        Msg::Save => {
+            let (movie, form) = match (&model.movie, &mut model.form) {
+                (RemoteData::Loaded(movie), Some(form)) => (movie, form),
+                _ => return,
+            };
+            let mut edited_movie = movie.clone();
+            form.errors = form
+                .texts
+                .iter()
+                .filter_map(|(field, text)| {
+                    field.set(&mut edited_movie, text).err().map(|error| (*field, error))
+                })
+                .collect();
+            if not(form.errors.is_empty()) {
+                return;
+            }
+            if let Some(review) = &mut edited_movie.review {
+                review.spoiler = form.review_spoiler;
+            }
+            model.form = None;
+            save_movie(model, edited_movie, orders);
+        },
+
+        Msg::ShowSpoilers => model.spoilers_visible = true,
+
+        // ------ Poster ------
+
+        Msg::OpenPosterUpload => {
+            model.poster_upload = Some(poster_upload::init(&mut orders.proxy(Msg::PosterUploadMsg)));
+        },
+        Msg::PosterUploadMsg(poster_upload::Msg::Close) => model.poster_upload = None,
+        Msg::PosterUploadMsg(poster_upload::Msg::Upload) => {
+            let images = model.poster_upload.take().and_then(|mut poster_upload| poster_upload.take_images());
+            if let Some(images) = images {
+                upload_poster(model, images, orders);
+            }
+        },
+        Msg::PosterUploadMsg(msg) => {
+            if let Some(poster_upload) = &mut model.poster_upload {
+                poster_upload::update(msg, poster_upload, &mut orders.proxy(Msg::PosterUploadMsg));
+            }
+        },
+        Msg::PosterUploaded(Ok(saved_poster)) => {
+            model.changes_status = request_finished(&model.changes_status);
+            model.saving = false;
+            let set_poster = |movie: &mut Movie| {
+                movie.poster_url = Some(saved_poster.poster_url.clone());
+                movie.thumbnail_url = Some(saved_poster.thumbnail_url.clone());
+                movie.version = saved_poster.version;
+            };
+            if let Some(saved_movie) = &mut model.saved_movie {
+                set_poster(saved_movie);
+            }
+            if let RemoteData::Loaded(movie) = &mut model.movie {
+                set_poster(movie);
+            }
+            send_queued_changes(model, orders);
+        },
+        Msg::PosterUploaded(Err(fetch_error)) => {
+            model.changes_status = request_finished(&model.changes_status);
+            model.saving = false;
+            model.errors.push(fetch_error);
+            send_queued_changes(model, orders);
+        },
+
+        // ------ Delete ------
+
+        Msg::Delete => {
+            let movie_id = model.movie_id;
+            model.changes_status = request_started(&model.changes_status);
+            orders.perform_cmd(async move { Msg::Deleted(movies::delete_movie(movie_id).await.err()) });
+        },
+        Msg::Deleted(None) => {
+            model.changes_status = request_finished(&model.changes_status);
+            orders.notify(subs::UrlRequested::new(crate::Urls::new(&model.base_url).movies()));
+        },
+        Msg::Deleted(Some(fetch_error)) => {
+            model.changes_status = request_finished(&model.changes_status);
+            model.errors.push(fetch_error);
+        },
+    }
+}
+
+fn save_movie(model: &mut Model, movie: Movie, orders: &mut impl Orders<Msg>) {
+    model.movie = RemoteData::Loaded(movie.clone());
+    update_similar_movies(model);
+    if model.saving {
+        model.save_queued = true;
+        return;
+    }
+    model.saving = true;
+    let movie_id = model.movie_id;
+    model.changes_status = request_started(&model.changes_status);
+    orders.perform_cmd(async move {
+        Msg::ChangesSaved(movie.clone(), movies::patch_movie(movie_id, movie).await)
+    });
+}
+
+fn update_similar_movies(model: &mut Model) {
+    model.similar_movies = match (&model.movie, &model.movies) {
+        (RemoteData::Loaded(movie), RemoteData::Loaded(movies)) => {
+            recommendations::more_like_this(model.movie_id, movie, movies, SIMILAR_MOVIES)
+        },
+        _ => Vec::new(),
+    };
+}
+
+/// The upload changes the movie version, so it waits for the save in flight like another save.
+fn upload_poster(
+    model: &mut Model,
+    (poster, thumbnail): (web_sys::Blob, web_sys::Blob),
+    orders: &mut impl Orders<Msg>,
+) {
+    if model.saving {
+        model.queued_poster = Some((poster, thumbnail));
+        return;
+    }
+    model.saving = true;
+    let movie_id = model.movie_id;
+    model.changes_status = request_started(&model.changes_status);
+    orders.perform_cmd(async move {
+        Msg::PosterUploaded(movies::put_poster(movie_id, poster, thumbnail).await)
+    });
+}
+
+/// Sends the changes made while the previous save or upload was in flight, with the current version.
+fn send_queued_changes(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if let Some(images) = model.queued_poster.take() {
+        upload_poster(model, images, orders);
+    }
+    if not(model.save_queued) {
+        return;
+    }
+    model.save_queued = false;
+    if let RemoteData::Loaded(movie) = &model.movie {
+        let movie = movie.clone();
+        save_movie(model, movie, orders);
+    }
+}
+
+fn request_started(changes_status: &ChangesStatus) -> ChangesStatus {
+    match changes_status {
+        ChangesStatus::Saving { requests_in_flight } => {
+            ChangesStatus::Saving { requests_in_flight: requests_in_flight + 1 }
+        },
+        ChangesStatus::NoChanges | ChangesStatus::Saved(_) => {
+            ChangesStatus::Saving { requests_in_flight: 1 }
+        },
+    }
+}
+
+fn request_finished(changes_status: &ChangesStatus) -> ChangesStatus {
+    match changes_status {
+        ChangesStatus::Saving { requests_in_flight } if *requests_in_flight > 1 => {
+            ChangesStatus::Saving { requests_in_flight: requests_in_flight - 1 }
+        },
+        _ => ChangesStatus::Saved(Local::now()),
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        s()
+            .py(rem(1.5))
+            .px(rem(0.75)),
+        a![
+            attrs!{At::Href => crate::Urls::new(&model.base_url).movies()},
+            "← Movies",
+        ],
+        view_changes_status(&model.changes_status),
+        view_errors(&model.errors),
+        model.conflict.as_ref().map(|conflict| conflict::view(conflict).map_msg(Msg::ConflictMsg)),
+        model.poster_upload.as_ref().map(|poster_upload| poster_upload::view(poster_upload).map_msg(Msg::PosterUploadMsg)),
+        match &model.movie {
+            RemoteData::NotAsked => empty![],
+            RemoteData::Loading => view_message("Loading movie..."),
+            RemoteData::Loaded(movie) => match &model.form {
+                Some(form) => view_form(form),
+                None => div![
+                    view_movie(model.movie_id, movie, model.spoilers_visible, &model.base_url),
+                    view_more_like_this(&model.similar_movies, &model.movies, &model.base_url),
+                ],
+            },
+        }
+    ]
+}
+
+fn view_changes_status(changes_status: &ChangesStatus) -> Node<Msg> {
+    let text = match changes_status {
+        ChangesStatus::NoChanges => return empty![],
+        ChangesStatus::Saving { .. } => "Saving…".to_owned(),
+        ChangesStatus::Saved(date_time) => format!("Saved at {}", date_time.format("%H:%M")),
+    };
+    p![
+        s()
+            .color("#7a7a7a")
+            .font_size(rem(0.875))
+            .mb(rem(0.75))
+            .text_align(CssTextAlign::Right),
+        text
+    ]
+}
+
+fn view_errors(errors: &[FetchError]) -> Node<Msg> {
+    if errors.is_empty() {
+        return empty![];
+    }
+    div![
+        C!["notification"],
+        s()
+            .bg_color("#feecf0")
+            .color("#cc0f35")
+            .border_radius(px(4))
+            .my(rem(1.5))
+            .py(rem(1.25))
+            .px(rem(1.5)),
+        ul![
+            errors.iter().map(|error| li![format!("{:?}", error)])
+        ],
+        button![
+            C!["button"],
+            s()
+                .mt(rem(0.75))
+                .cursor(CssCursor::Pointer),
+            "Clear errors",
+            ev(Ev::Click, |_| Msg::ClearErrors),
+        ]
+    ]
+}
+
+fn view_message(message: &str) -> Node<Msg> {
+    p![
+        s()
+            .color("#7a7a7a")
+            .text_align(CssTextAlign::Center)
+            .py(rem(3)),
+        message
+    ]
+}
+
+fn view_movie(movie_id: MovieId, movie: &Movie, spoilers_visible: bool, base_url: &Url) -> Node<Msg> {
+    div![
+        s()
+            .display(CssDisplay::Flex)
+            .mt(rem(1.5)),
+        div![
+            s()
+                .flex_shrink("0")
+                .mr(rem(1.5))
+                .w(px(200)),
+            div![
+                s()
+                    .bg_color("#f5f5f5")
+                    .h(px(300))
+                    .w(px(200)),
+                movie.poster_url.as_ref().map(|poster_url| img![
+                    s()
+                        .h(px(300))
+                        .w(px(200))
+                        .raw("object-fit: cover;"),
+                    attrs!{
+                        At::Src => poster_url,
+                        At::Alt => "Poster",
+                    },
+                ])
+            ],
+            button![
+                C!["button"],
+                s()
+                    .cursor(CssCursor::Pointer)
+                    .mt(rem(0.5))
+                    .w(pc(100)),
+                "Change poster",
+                ev(Ev::Click, |_| Msg::OpenPosterUpload),
+            ],
+        ],
+        div![
+            s()
+                .flex_grow("1"),
+            h1![
+                s()
+                    .font_size(rem(2))
+                    .font_weight("600"),
+                &movie.title,
+            ],
+            dl![
+                s()
+                    .display(CssDisplay::Grid)
+                    .grid_template_columns("max-content auto")
+                    .raw(format!("grid-gap: {} {};", rem(0.25), rem(1)).as_str())
+                    .my(rem(1)),
+                view_fact("Year", movie.year.map(|year| year.to_string())),
+                view_genres(&movie.genres, base_url),
+                view_fact("Runtime", movie.runtime.map(movies::format_runtime)),
+                view_fact("Cast", if movie.cast.is_empty() { None } else { Some(movie.cast.join(", ")) }),
+                view_fact("Crew", if movie.crew.is_empty() { None } else { Some(movie.crew.join(", ")) }),
+                view_fact("Tags", if movie.tags.is_empty() {
+                    None
+                } else {
+                    Some(movie.tags.iter().map(|tag| format!("#{}", tag)).collect::<Vec<_>>().join(" "))
+                }),
+                view_fact("Your rating", movie.rating.map(|rating| format!("★ {}", movies::format_stars(rating)))),
+                view_fact("Average rating", movie.average_rating.map(|average_rating| {
+                    format!("★ {:.1}", average_rating / 2.)
+                })),
+                view_fact("Status", Some(movie.watch_status.label().to_owned())),
+                view_fact("Watch history", if movie.watch_history.is_empty() {
+                    None
+                } else {
+                    Some(movie
+                        .watch_history
+                        .iter()
+                        .map(|event| event.watched_on.format("%Y-%m-%d").to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "))
+                }),
+                view_fact("Added", Some(movies::added_at(movie_id).format("%Y-%m-%d %H:%M").to_string())),
+                view_fact("Id", Some(movie_id.to_string())),
+            ],
+            movie.synopsis.as_ref().map(|synopsis| p![synopsis]),
+            movie.review.as_ref().map(|review| view_review(review, spoilers_visible)),
+            div![
+                s()
+                    .mt(rem(1.5)),
+                button![
+                    C!["button"],
+                    s()
+                        .cursor(CssCursor::Pointer)
+                        .mr(rem(0.5)),
+                    "Edit",
+                    ev(Ev::Click, |_| Msg::StartEdit),
+                ],
+                button![
+                    C!["button"],
+                    s()
+                        .color("#f14668")
+                        .cursor(CssCursor::Pointer),
+                    "Delete",
+                    ev(Ev::Click, |_| Msg::Delete),
+                ],
+            ],
+        ],
+    ]
+}
+
+fn view_more_like_this(
+    similar_movies: &[(MovieId, f64)],
+    movies: &RemoteData<BTreeMap<MovieId, Movie>>,
+    base_url: &Url,
+) -> Node<Msg> {
+    let movies = match movies {
+        RemoteData::Loaded(movies) => movies,
+        RemoteData::NotAsked | RemoteData::Loading => return empty![],
+    };
+    if similar_movies.is_empty() {
+        return empty![];
+    }
+    div![
+        s()
+            .border_top("1px solid #ededed")
+            .mt(rem(1.5))
+            .pt(rem(1)),
+        h2![
+            s()
+                .font_size(rem(1.25))
+                .font_weight("600")
+                .mb(rem(0.5)),
+            "More like this",
+        ],
+        ul![
+            similar_movies.iter().filter_map(|(similar_id, _)| {
+                let similar_movie = movies.get(similar_id)?;
+                Some(li![
+                    a![
+                        attrs!{At::Href => crate::Urls::new(base_url).movie(*similar_id)},
+                        &similar_movie.title,
+                    ],
+                    similar_movie.year.map(|year| span![
+                        s()
+                            .color("#7a7a7a")
+                            .ml(rem(0.5)),
+                        year.to_string(),
+                    ]),
+                ])
+            })
+        ],
+    ]
+}
+
+fn view_review(review: &Review, spoilers_visible: bool) -> Node<Msg> {
+    div![
+        s()
+            .border_left("3px solid #dbdbdb")
+            .mt(rem(1))
+            .pl(rem(1)),
+        h2![
+            s()
+                .font_size(rem(1.25))
+                .font_weight("600"),
+            "Your review",
+        ],
+        if review.spoiler && not(spoilers_visible) {
+            p![
+                "This review contains spoilers. ",
+                button![
+                    C!["button"],
+                    s()
+                        .cursor(CssCursor::Pointer),
+                    "Show",
+                    ev(Ev::Click, |_| Msg::ShowSpoilers),
+                ]
+            ]
+        } else {
+            p![&review.text]
+        }
+    ]
+}
+
+fn view_fact(label: &str, value: Option<String>) -> Vec<Node<Msg>> {
+    vec![
+        dt![
+            s()
+                .font_weight("600"),
+            label
+        ],
+        dd![
+            value.unwrap_or_else(|| "—".to_owned())
+        ],
+    ]
+}
+
+fn view_genres(genres: &[String], base_url: &Url) -> Vec<Node<Msg>> {
+    if genres.is_empty() {
+        return view_fact("Genres", None);
+    }
+    vec![
+        dt![
+            s()
+                .font_weight("600"),
+            "Genres"
+        ],
+        dd![
+            genres.iter().enumerate().map(|(index, genre)| {
+                let filters = Filters { genre: Some(genre.clone()), ..Filters::default() };
+                span![
+                    IF!(index > 0 => ", "),
+                    a![
+                        attrs!{At::Href => crate::Urls::new(base_url).movies_with_filters(&filters)},
+                        genre,
+                    ]
+                ]
+            })
+        ],
+    ]
+}
+
+fn view_form(form: &Form) -> Node<Msg> {
+    form![
+        s()
+            .mt(rem(1.5)),
+        ev(Ev::Submit, |event| {
+            event.prevent_default();
+            Msg::Save
+        }),
+        form.texts.iter().map(|(field, text)| {
+            let field = *field;
+            let error = form.errors.get(&field);
+            div![
+                s()
+                    .mb(rem(0.5)),
+                label![
+                    s()
+                        .display(CssDisplay::Block)
+                        .font_weight("600"),
+                    field.label(),
+                    input![
+                        s()
+                            .border("1px solid #dbdbdb")
+                            .border_radius(px(4))
+                            .display(CssDisplay::Block)
+                            .font_weight("400")
+                            .px(rem(0.5))
+                            .py(rem(0.25))
+                            .w(pc(100)),
+                        if error.is_some() {
+                            s().border_color("#f14668")
+                        } else {
+                            s()
+                        },
+                        attrs!{At::Value => text},
+                        input_ev(Ev::Input, move |text| Msg::FieldChanged(field, text)),
+                    ],
+                ],
+                error.map(|error| p![
+                    s()
+                        .color("#f14668")
+                        .font_size(rem(0.75)),
+                    error
+                ])
+            ]
+        }),
+        label![
+            s()
+                .cursor(CssCursor::Pointer)
+                .display(CssDisplay::Block)
+                .mb(rem(0.5)),
+            input![
+                s()
+                    .mr(rem(0.25)),
+                attrs!{
+                    At::Type => "checkbox",
+                    At::Checked => form.review_spoiler.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::ReviewSpoilerToggled),
+            ],
+            "Review contains spoilers",
+        ],
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .mr(rem(0.5))
+                .mt(rem(0.5)),
+            attrs!{At::Type => "submit"},
+            "Save",
+        ],
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .mt(rem(0.5)),
+            attrs!{At::Type => "button"},
+            "Cancel",
+            ev(Ev::Click, |_| Msg::CancelEdit),
+        ],
+    ]
+}
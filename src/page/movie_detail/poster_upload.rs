@@ -0,0 +1,369 @@
+use seed::{prelude::*, *};
+
+use seed_style::{pc, px, rem};
+use seed_style::*;
+
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement};
+
+/// The longer side of uploaded posters, in pixels.
+const MAX_POSTER_SIZE: u32 = 600;
+/// Twice the poster size in the movie list, so thumbnails stay sharp on HiDPI screens.
+const MAX_THUMBNAIL_SIZE: u32 = 192;
+const JPEG_QUALITY: f64 = 0.85;
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(orders: &mut impl Orders<Msg>) -> Model {
+    Model {
+        poster: None,
+        drag_over: false,
+        resizing: false,
+        latest_resize: 0,
+        error: None,
+        _paste_stream: orders.stream_with_handle(streams::window_event(Ev::Paste, on_paste)),
+    }
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    poster: Option<ResizedPoster>,
+    /// An image is dragged over the drop zone.
+    drag_over: bool,
+    resizing: bool,
+    /// Numbers resizes; results of older ones are ignored, e.g. when a slow large image finishes last.
+    latest_resize: u32,
+    error: Option<String>,
+    _paste_stream: StreamHandle,
+}
+
+impl Model {
+    /// The poster and its thumbnail, ready for upload.
+    pub fn take_images(&mut self) -> Option<(Blob, Blob)> {
+        self.poster.take().map(|poster| (poster.poster.clone(), poster.thumbnail.clone()))
+    }
+}
+
+pub struct ResizedPoster {
+    poster: Blob,
+    thumbnail: Blob,
+    /// Object URL of `poster`.
+    preview_url: String,
+    width: u32,
+    height: u32,
+}
+
+impl Drop for ResizedPoster {
+    fn drop(&mut self) {
+        let _ = web_sys::Url::revoke_object_url(&self.preview_url);
+    }
+}
+
+/// The size fitting into a `max_size` square, with the same aspect ratio. Images are never enlarged.
+fn fit_within(width: u32, height: u32, max_size: u32) -> (u32, u32) {
+    let longer_side = width.max(height);
+    if longer_side <= max_size {
+        return (width, height);
+    }
+    let scale = f64::from(max_size) / f64::from(longer_side);
+    let scaled = |side: u32| (f64::from(side) * scale).round().max(1.) as u32;
+    (scaled(width), scaled(height))
+}
+
+async fn resize_poster(file: web_sys::File) -> Result<ResizedPoster, String> {
+    if not(file.type_().starts_with("image/")) {
+        return Err(format!("\"{}\" isn't an image.", file.name()));
+    }
+    let image = load_image(&file).await?;
+    let (width, height) = fit_within(image.natural_width(), image.natural_height(), MAX_POSTER_SIZE);
+    let poster = resize_image(&image, width, height).await?;
+    let (thumbnail_width, thumbnail_height) = fit_within(width, height, MAX_THUMBNAIL_SIZE);
+    let thumbnail = resize_image(&image, thumbnail_width, thumbnail_height).await?;
+    let preview_url = web_sys::Url::create_object_url_with_blob(&poster)
+        .map_err(|error| format!("Cannot show the preview: {:?}", error))?;
+    Ok(ResizedPoster { poster, thumbnail, preview_url, width, height })
+}
+
+async fn load_image(file: &web_sys::File) -> Result<HtmlImageElement, String> {
+    let url = web_sys::Url::create_object_url_with_blob(file)
+        .map_err(|error| format!("Cannot read the file: {:?}", error))?;
+    let image = HtmlImageElement::new().map_err(|error| format!("Cannot create an image: {:?}", error))?;
+    image.set_src(&url);
+    let decoded = JsFuture::from(image.decode()).await;
+    let _ = web_sys::Url::revoke_object_url(&url);
+    decoded
+        .map(|_| image)
+        .map_err(|_| format!("\"{}\" isn't a supported image.", file.name()))
+}
+
+/// Draws the image scaled on a canvas and compresses it to JPEG.
+async fn resize_image(image: &HtmlImageElement, width: u32, height: u32) -> Result<Blob, String> {
+    let canvas = document()
+        .create_element("canvas")
+        .ok()
+        .and_then(|canvas| canvas.dyn_into::<HtmlCanvasElement>().ok())
+        .ok_or("Cannot create a canvas.")?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let context = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+        .ok_or("Cannot draw on the canvas.")?;
+
+    let (width, height) = (f64::from(width), f64::from(height));
+    // JPEG doesn't support transparency; transparent pixels would turn black.
+    context.set_fill_style(&JsValue::from("white"));
+    context.fill_rect(0., 0., width, height);
+    context
+        .draw_image_with_html_image_element_and_dw_and_dh(image, 0., 0., width, height)
+        .map_err(|error| format!("Cannot resize the image: {:?}", error))?;
+
+    let blob = JsFuture::from(js_sys::Promise::new(&mut |resolve, reject| {
+        let on_blob = Closure::once_into_js(move |blob: JsValue| {
+            let _ = resolve.call1(&JsValue::NULL, &blob);
+        });
+        if let Err(error) = canvas.to_blob_with_type_and_encoder_options(
+            on_blob.unchecked_ref(),
+            "image/jpeg",
+            &JsValue::from(JPEG_QUALITY),
+        ) {
+            let _ = reject.call1(&JsValue::NULL, &error);
+        }
+    }))
+    .await
+    .map_err(|error| format!("Cannot compress the image: {:?}", error))?;
+    // `toBlob` passes `null` when the canvas is empty.
+    blob.dyn_into::<Blob>().map_err(|_| "Cannot compress the image.".to_owned())
+}
+
+/// Pasted images are accepted anywhere on the page while the dialog is open.
+fn on_paste(event: web_sys::Event) -> Option<Msg> {
+    let event = event.unchecked_into::<web_sys::ClipboardEvent>();
+    let file = event.clipboard_data()?.files()?.get(0)?;
+    event.prevent_default();
+    Some(Msg::FileSelected(Some(file)))
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    FileSelected(Option<web_sys::File>),
+    /// Contains the resize number.
+    Resized(u32, Result<ResizedPoster, String>),
+    DragEntered,
+    DragLeft,
+
+    // ------ handled by the parent ------
+
+    Upload,
+    Close,
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::FileSelected(Some(file)) => {
+            model.drag_over = false;
+            model.resizing = true;
+            model.error = None;
+            model.latest_resize = model.latest_resize.wrapping_add(1);
+            let resize = model.latest_resize;
+            orders.perform_cmd(async move { Msg::Resized(resize, resize_poster(file).await) });
+        },
+        Msg::FileSelected(None) => model.drag_over = false,
+        Msg::Resized(resize, _) if resize != model.latest_resize => {
+            orders.skip();
+        },
+        Msg::Resized(_, Ok(poster)) => {
+            model.resizing = false;
+            model.poster = Some(poster);
+        },
+        Msg::Resized(_, Err(error)) => {
+            model.resizing = false;
+            model.error = Some(error);
+        },
+        // `dragover` fires every few hundred milliseconds.
+        Msg::DragEntered if model.drag_over => {
+            orders.skip();
+        },
+        Msg::DragEntered => model.drag_over = true,
+        Msg::DragLeft => model.drag_over = false,
+
+        Msg::Upload | Msg::Close => {},
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .bg_color(rgba(10, 10, 10, 0.86))
+            .bottom("0")
+            .display(CssDisplay::Flex)
+            .justify_content(CssJustifyContent::Center)
+            .left("0")
+            .position(CssPosition::Fixed)
+            .right("0")
+            .top("0")
+            .z_index("40"),
+        div![
+            s()
+                .bg_color("white")
+                .border_radius(px(6))
+                .max_h("90vh")
+                .overflow_y("auto")
+                .p(rem(1.5))
+                .w(pc(90))
+                .max_w(px(500)),
+            h2![
+                s()
+                    .font_size(rem(1.5))
+                    .font_weight("600")
+                    .mb(rem(1)),
+                "Change poster",
+            ],
+            view_drop_zone(model),
+            model.error.as_ref().map(|error| p![
+                s()
+                    .color("#f14668")
+                    .mt(rem(0.5)),
+                error
+            ]),
+            div![
+                s()
+                    .mt(rem(1)),
+                button![
+                    C!["button"],
+                    s()
+                        .cursor(CssCursor::Pointer)
+                        .mr(rem(0.5)),
+                    attrs!{At::Disabled => model.poster.is_none().as_at_value()},
+                    "Upload",
+                    ev(Ev::Click, |_| Msg::Upload),
+                ],
+                button![
+                    C!["button"],
+                    s()
+                        .cursor(CssCursor::Pointer),
+                    "Cancel",
+                    ev(Ev::Click, |_| Msg::Close),
+                ],
+            ],
+        ]
+    ]
+}
+
+fn view_drop_zone(model: &Model) -> Node<Msg> {
+    label![
+        s()
+            .align_items(CssAlignItems::Center)
+            .border("2px dashed #dbdbdb")
+            .border_radius(px(6))
+            .cursor(CssCursor::Pointer)
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Column)
+            .justify_content(CssJustifyContent::Center)
+            .min_h(px(200))
+            .p(rem(1))
+            .text_align(CssTextAlign::Center),
+        if model.drag_over {
+            s()
+                .bg_color("#f0f8ff")
+                .border_color("#3273dc")
+        } else {
+            s()
+        },
+        drag_ev(Ev::DragOver, |event| {
+            // Allows dropping.
+            event.prevent_default();
+            Msg::DragEntered
+        }),
+        ev(Ev::DragLeave, |_| Msg::DragLeft),
+        drag_ev(Ev::Drop, |event| {
+            event.prevent_default();
+            Msg::FileSelected(event.data_transfer().and_then(|data_transfer| data_transfer.files()).and_then(|files| files.get(0)))
+        }),
+        match &model.poster {
+            Some(poster) => div![
+                img![
+                    s()
+                        .max_h(px(300))
+                        .max_w(pc(100)),
+                    attrs!{
+                        At::Src => poster.preview_url,
+                        At::Alt => "Poster preview",
+                    },
+                ],
+                p![
+                    s()
+                        .color("#7a7a7a")
+                        .font_size(rem(0.875)),
+                    format!(
+                        "{} × {} px, {} kB",
+                        poster.width,
+                        poster.height,
+                        (poster.poster.size() / 1024.).ceil(),
+                    ),
+                ],
+            ],
+            None if model.resizing => p!["Resizing…"],
+            None => p![
+                s()
+                    .color("#7a7a7a"),
+                "Choose an image, drop it here or paste it.",
+            ],
+        },
+        input![
+            s()
+                .display(CssDisplay::None),
+            attrs!{
+                At::Type => "file",
+                At::Accept => "image/*",
+            },
+            ev(Ev::Change, |event| {
+                let input = event
+                    .target()
+                    .and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok());
+                Msg::FileSelected(input.and_then(|input| input.files()).and_then(|files| files.get(0)))
+            }),
+        ],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_within_scales_landscape_images_to_the_width() {
+        assert_eq!(fit_within(3000, 2000, MAX_POSTER_SIZE), (600, 400));
+        assert_eq!(fit_within(1000, 333, MAX_POSTER_SIZE), (600, 200));
+        // Very thin images keep at least one pixel.
+        assert_eq!(fit_within(5000, 2, MAX_POSTER_SIZE), (600, 1));
+    }
+
+    #[test]
+    fn fit_within_scales_portrait_images_to_the_height() {
+        assert_eq!(fit_within(1000, 1500, MAX_POSTER_SIZE), (400, 600));
+        assert_eq!(fit_within(400, 600, MAX_THUMBNAIL_SIZE), (128, 192));
+    }
+
+    #[test]
+    fn fit_within_keeps_small_images() {
+        assert_eq!(fit_within(300, 200, MAX_POSTER_SIZE), (300, 200));
+        assert_eq!(fit_within(600, 600, MAX_POSTER_SIZE), (600, 600));
+        assert_eq!(fit_within(0, 0, MAX_POSTER_SIZE), (0, 0));
+    }
+}
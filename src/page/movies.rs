@@ -1,23 +1,250 @@
 use seed::{prelude::*, *};
 
+use seed_style::{pc, px, rem};
+use seed_style::*;
+
 use chrono::prelude::*;
-use ulid::Ulid;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::cmp::Ordering;
+use std::mem;
+
+mod virtual_list;
+use virtual_list::VirtualList;
+
+mod movie;
+pub use movie::{added_at, format_runtime, Movie, MovieId};
+
+mod watch_status;
+pub use watch_status::{WatchEvent, WatchStatus};
+
+mod rating;
+pub use rating::{format_stars, Review};
+
+mod import;
+
+mod export;
+use export::ExportFormat;
+
+mod duplicates;
+use duplicates::is_likely_duplicate;
+
+mod merge;
+
+mod picker;
 
-use std::collections::BTreeMap;
+pub mod conflict;
 
-type MovieId = Ulid;
+pub mod recommendations;
+
+mod history;
+use history::{History, Step};
+
+mod sync;
+pub use sync::is_conflict;
+use sync::SyncQueue;
+
+#[cfg(test)]
+pub mod fixtures;
+
+use super::lists::{self, List, ListId};
+
+const MOVIES: &str = "movies";
+const SAVE_EDITS_DEBOUNCE_MS: u32 = 800;
+/// Serializing the whole collection is expensive; changes in a row are cached at once.
+const CACHE_MOVIES_DEBOUNCE_MS: u32 = 1000;
+const UNDO_TOAST_MS: u32 = 6000;
+
+const ROW_HEIGHT: f64 = 160.;
+const OVERSCAN_ROWS: usize = 5;
+/// Used until the real viewport height is measured.
+const DEFAULT_VIEWPORT_HEIGHT: f64 = 800.;
+
+// ------ Url search keys ------
+
+const SEARCH: &str = "search";
+const GENRE: &str = "genre";
+const YEAR_FROM: &str = "year_from";
+const YEAR_TO: &str = "year_to";
+const WATCH_STATUS: &str = "status";
+const SORT: &str = "sort";
 
 // ------ ------
 //     Init
 // ------ ------
 
-pub fn init(url: Url, _: &mut impl Orders<Msg>) -> Model {
-    Model {
+pub fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
+    orders
+        .perform_cmd(async { Msg::MoviesFetched(request_movies().await) })
+        .perform_cmd(async { Msg::ListsFetched(lists::request_lists().await) });
+    let resize_stream = orders.stream_with_handle(streams::window_event(Ev::Resize, |_| Msg::MeasureViewport));
+    let key_down_stream = orders.stream_with_handle(streams::window_event(Ev::KeyDown, on_key_down));
+    let online_stream = orders.stream_with_handle(streams::window_event(Ev::Online, |_| Msg::WentOnline));
+    let offline_stream = orders.stream_with_handle(streams::window_event(Ev::Offline, |_| Msg::WentOffline));
+
+    let sync_queue = SyncQueue::load();
+    if not(sync_queue.is_empty()) {
+        orders.send_msg(Msg::ReplaySyncQueue);
+    }
+    // Cached movies are displayed until the server responds.
+    let clients = match sync::load_cached_movies() {
+        Some(mut movies) => {
+            // The page may have been closed before the last changes were cached.
+            sync_queue.apply(&mut movies);
+            orders.after_next_render(|_| Msg::MeasureViewport);
+            RemoteData::Loaded(movies)
+        },
+        None => RemoteData::Loading,
+    };
+
+    let mut model = Model {
         changes_status: ChangesStatus::NoChanges,
         errors: Vec::new(),
 
-        clients: RemoteData::NotAsked,
+        clients,
+        filters: Filters::from_url_search(url.search()),
+        sort: Sort::from_url_search(url.search()),
+        visible_movie_ids: Vec::new(),
+        genres: BTreeSet::new(),
+        url,
+        new_movie_form: NewMovieForm::default(),
+        movie_edit: None,
+        unsaved_movies: BTreeMap::new(),
+        saving_movies: BTreeSet::new(),
+        selected_movies: BTreeSet::new(),
+        selection_anchor: None,
+        bulk_tag: String::new(),
+        bulk_list: None,
+        lists: BTreeMap::new(),
+        save_edits_handle: None,
+        cache_movies_handle: None,
+        virtual_list: VirtualList::new(ROW_HEIGHT, OVERSCAN_ROWS, DEFAULT_VIEWPORT_HEIGHT),
+        viewport: ElRef::default(),
+        loaded_posters: BTreeSet::new(),
+        _resize_stream: resize_stream,
+        _key_down_stream: key_down_stream,
+        _online_stream: online_stream,
+        _offline_stream: offline_stream,
+        sync_queue,
+        replay_handle: None,
+        history: History::default(),
+        undo_toast_handle: None,
+        import: None,
+        import_review: None,
+        export_menu_visible: false,
+        duplicate_warning: None,
+        duplicate_groups: None,
+        merge: None,
+        picker: None,
+        conflicts: VecDeque::new(),
+    };
+    refresh_visible_movies(&mut model);
+    model
+}
+
+pub async fn request_movies() -> fetch::Result<BTreeMap<MovieId, Movie>> {
+    fetch(format!("{}/{}", crate::API_URL, MOVIES))
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
+async fn post_movie(movie_id: MovieId, movie: Movie) -> fetch::Result<()> {
+    Request::new(format!("{}/{}", crate::API_URL, MOVIES))
+        .method(Method::Post)
+        .json(&NewMovie { id: movie_id, movie: &movie })?
+        .fetch()
+        .await?
+        .check_status()
+        .map(drop)
+}
+
+pub async fn request_movie(movie_id: MovieId) -> fetch::Result<Movie> {
+    fetch(format!("{}/{}/{}", crate::API_URL, MOVIES, movie_id))
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
+pub async fn post_movies(movies: Vec<(MovieId, Movie)>) -> fetch::Result<()> {
+    let movies = movies
+        .iter()
+        .map(|(movie_id, movie)| NewMovie { id: *movie_id, movie })
+        .collect::<Vec<_>>();
+    Request::new(format!("{}/{}/bulk", crate::API_URL, MOVIES))
+        .method(Method::Post)
+        .json(&movies)?
+        .fetch()
+        .await?
+        .check_status()
+        .map(drop)
+}
+
+/// The server refuses the change with `412 Precondition Failed`
+/// when the movie has been changed since `movie.version`.
+/// Returns the new version.
+pub async fn patch_movie(movie_id: MovieId, movie: Movie) -> fetch::Result<u64> {
+    Request::new(format!("{}/{}/{}", crate::API_URL, MOVIES, movie_id))
+        .method(Method::Patch)
+        .header(Header::custom("If-Match", movie.version.to_string()))
+        .json(&movie)?
+        .fetch()
+        .await?
+        .check_status()?
+        .json::<SavedMovie>()
+        .await
+        .map(|saved_movie| saved_movie.version)
+}
+
+/// The server updates the surviving movie, moves list memberships of the merged one to it
+/// and deletes the merged movie - all in one transaction.
+/// Returns the new version of the surviving movie.
+async fn post_merge(surviving_id: MovieId, merged_id: MovieId, movie: Movie) -> fetch::Result<u64> {
+    #[derive(Serialize)]
+    struct Merge<'a> {
+        merged_id: MovieId,
+        movie: &'a Movie,
     }
+    Request::new(format!("{}/{}/{}/merge", crate::API_URL, MOVIES, surviving_id))
+        .method(Method::Post)
+        .header(Header::custom("If-Match", movie.version.to_string()))
+        .json(&Merge { merged_id, movie: &movie })?
+        .fetch()
+        .await?
+        .check_status()?
+        .json::<SavedMovie>()
+        .await
+        .map(|saved_movie| saved_movie.version)
+}
+
+/// Both images are JPEGs already resized in the browser.
+pub async fn put_poster(movie_id: MovieId, poster: web_sys::Blob, thumbnail: web_sys::Blob) -> fetch::Result<SavedPoster> {
+    let form_data = web_sys::FormData::new().map_err(FetchError::RequestError)?;
+    form_data.append_with_blob_and_filename("poster", &poster, "poster.jpg").map_err(FetchError::RequestError)?;
+    form_data
+        .append_with_blob_and_filename("thumbnail", &thumbnail, "thumbnail.jpg")
+        .map_err(FetchError::RequestError)?;
+
+    Request::new(format!("{}/{}/{}/poster", crate::API_URL, MOVIES, movie_id))
+        .method(Method::Put)
+        .body(form_data.into())
+        .fetch()
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
+pub async fn delete_movie(movie_id: MovieId) -> fetch::Result<()> {
+    Request::new(format!("{}/{}/{}", crate::API_URL, MOVIES, movie_id))
+        .method(Method::Delete)
+        .fetch()
+        .await?
+        .check_status()
+        .map(drop)
 }
 
 // ------ ------
@@ -26,9 +253,58 @@ pub fn init(url: Url, _: &mut impl Orders<Msg>) -> Model {
 
 pub struct Model {
     changes_status: ChangesStatus,
-    errors: Vec<FetchError>,
+    /// Failed requests and exports, ready to display.
+    errors: Vec<String>,
 
     clients: RemoteData<BTreeMap<MovieId, Movie>>,
+    filters: Filters,
+    sort: Sort,
+    /// Movies matching the filters, in the sort order - i.e. what the user sees in the list.
+    /// Recomputed by `refresh_visible_movies` when the movies, filters or sort change, not on every render or scroll.
+    visible_movie_ids: Vec<MovieId>,
+    /// Genres of all movies, offered by the genre filter.
+    genres: BTreeSet<String>,
+    url: Url,
+    new_movie_form: NewMovieForm,
+    movie_edit: Option<MovieEdit>,
+    /// Movies changed locally but not sent yet, with their last saved state for rollbacks.
+    unsaved_movies: BTreeMap<MovieId, Movie>,
+    /// Movies with an update in flight; their next update waits for the new version.
+    saving_movies: BTreeSet<MovieId>,
+    selected_movies: BTreeSet<MovieId>,
+    /// The last clicked movie; shift-click selects the range between it and the clicked movie.
+    selection_anchor: Option<MovieId>,
+    bulk_tag: String,
+    bulk_list: Option<ListId>,
+    /// Targets of the "Add to list" bulk action.
+    lists: BTreeMap<ListId, List>,
+    save_edits_handle: Option<CmdHandle>,
+    cache_movies_handle: Option<CmdHandle>,
+    virtual_list: VirtualList,
+    viewport: ElRef<web_sys::Element>,
+    /// Poster URLs already loaded; rows scrolled into view again don't show the placeholder.
+    loaded_posters: BTreeSet<String>,
+    _resize_stream: StreamHandle,
+    _key_down_stream: StreamHandle,
+    _online_stream: StreamHandle,
+    _offline_stream: StreamHandle,
+    sync_queue: SyncQueue,
+    /// The replay retry after a network error; dropping it cancels the retry.
+    replay_handle: Option<CmdHandle>,
+    history: History,
+    /// The undo toast is visible until the timeout fires.
+    undo_toast_handle: Option<CmdHandle>,
+    import: Option<import::Model>,
+    import_review: Option<ImportReview>,
+    export_menu_visible: bool,
+    duplicate_warning: Option<DuplicateWarning>,
+    /// The "Find duplicates" report; `None` when it's closed.
+    duplicate_groups: Option<Vec<Vec<MovieId>>>,
+    merge: Option<merge::Model>,
+    /// "Pick something for tonight"; `None` when it's closed.
+    picker: Option<picker::Model>,
+    /// Changes refused because of newer server movies; only the first one is displayed.
+    conflicts: VecDeque<conflict::Model>,
 }
 
 enum RemoteData<T> {
@@ -39,12 +315,521 @@ enum RemoteData<T> {
 
 enum ChangesStatus {
     NoChanges,
-    Saving { requests_in_flight: usize },
+    /// `failed` - one of the finished requests has failed.
+    Saving { requests_in_flight: usize, failed: bool },
     Saved(DateTime<Local>),
+    /// Some changes have been rolled back, queued or wait in the conflict dialog; the errors tell more.
+    NotSaved,
+}
+
+#[derive(Serialize)]
+struct NewMovie<'a> {
+    id: MovieId,
+    #[serde(flatten)]
+    movie: &'a Movie,
+}
+
+/// The server's response to a movie update.
+#[derive(Deserialize)]
+struct SavedMovie {
+    version: u64,
+}
+
+/// The server's response to a poster upload.
+#[derive(Deserialize)]
+pub struct SavedPoster {
+    pub poster_url: String,
+    pub thumbnail_url: String,
+    pub version: u64,
+}
+
+// ------ Filters ------
+
+#[derive(Default)]
+pub struct Filters {
+    pub search: String,
+    pub genre: Option<String>,
+    pub year_from: Option<i32>,
+    pub year_to: Option<i32>,
+    pub watch_status: Option<WatchStatus>,
+}
+
+impl Filters {
+    pub fn from_url_search(url_search: &UrlSearch) -> Self {
+        let first = |key: &str| url_search.get(key).and_then(|values| values.first());
+        Self {
+            search: first(SEARCH).cloned().unwrap_or_default(),
+            genre: first(GENRE).cloned(),
+            year_from: first(YEAR_FROM).and_then(|year| year.parse().ok()),
+            year_to: first(YEAR_TO).and_then(|year| year.parse().ok()),
+            watch_status: first(WATCH_STATUS).and_then(|watch_status| WatchStatus::parse(watch_status)),
+        }
+    }
+
+    pub fn write_url_search(&self, url_search: &mut UrlSearch) {
+        let mut set = |key: &str, value: Option<String>| {
+            url_search.remove(key);
+            if let Some(value) = value {
+                url_search.push_value(key, value);
+            }
+        };
+        set(SEARCH, non_empty(&self.search));
+        set(GENRE, self.genre.clone());
+        set(YEAR_FROM, self.year_from.map(|year| year.to_string()));
+        set(YEAR_TO, self.year_to.map(|year| year.to_string()));
+        set(WATCH_STATUS, self.watch_status.map(|watch_status| watch_status.as_str().to_owned()));
+    }
+
+    fn is_active(&self) -> bool {
+        not(self.search.trim().is_empty())
+            || self.genre.is_some()
+            || self.year_from.is_some()
+            || self.year_to.is_some()
+            || self.watch_status.is_some()
+    }
+
+    fn matches(&self, movie: &Movie) -> bool {
+        let search = self.search.trim().to_lowercase();
+        let matches_search = search.is_empty()
+            || movie.title.to_lowercase().contains(&search)
+            || movie.synopsis.as_ref().map_or(false, |synopsis| synopsis.to_lowercase().contains(&search));
+
+        let matches_genre = self.genre.as_ref().map_or(true, |genre| movie.genres.contains(genre));
+
+        let matches_year = match (self.year_from, self.year_to, movie.year) {
+            (None, None, _) => true,
+            (_, _, None) => false,
+            (year_from, year_to, Some(year)) => {
+                year_from.map_or(true, |year_from| year >= year_from)
+                    && year_to.map_or(true, |year_to| year <= year_to)
+            }
+        };
+
+        let matches_watch_status = self
+            .watch_status
+            .map_or(true, |watch_status| movie.watch_status == watch_status);
+
+        matches_search && matches_genre && matches_year && matches_watch_status
+    }
+}
+
+// ------ Sort ------
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Title,
+    Year,
+    Rating,
+    Runtime,
+    Added,
+}
+
+impl SortKey {
+    const ALL: [Self; 5] = [Self::Title, Self::Year, Self::Rating, Self::Runtime, Self::Added];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Year => "year",
+            Self::Rating => "rating",
+            Self::Runtime => "runtime",
+            Self::Added => "added",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Year => "Year",
+            Self::Rating => "Rating",
+            Self::Runtime => "Runtime",
+            Self::Added => "Date added",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|key| key.as_str() == text)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            Self::Ascending => ordering,
+            Self::Descending => ordering.reverse(),
+        }
+    }
+
+    fn toggled(self) -> Self {
+        match self {
+            Self::Ascending => Self::Descending,
+            Self::Descending => Self::Ascending,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct SortBy {
+    pub key: SortKey,
+    pub order: SortOrder,
+}
+
+impl SortBy {
+    /// Movies without the compared value are always at the end.
+    fn compare(self, a: &SortRow, b: &SortRow) -> Ordering {
+        fn present_first<T: Ord>(a: Option<T>, b: Option<T>, order: SortOrder) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => order.apply(a.cmp(&b)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+        match self.key {
+            SortKey::Title => self.order.apply(a.lowercase_title.cmp(&b.lowercase_title)),
+            SortKey::Year => present_first(a.movie.year, b.movie.year, self.order),
+            SortKey::Rating => present_first(a.movie.rating, b.movie.rating, self.order),
+            SortKey::Runtime => present_first(a.movie.runtime, b.movie.runtime, self.order),
+            SortKey::Added => self.order.apply(a.movie_id.cmp(&b.movie_id)),
+        }
+    }
+
+    /// Format: `year` (ascending) or `-year` (descending).
+    fn to_url_value(self) -> String {
+        match self.order {
+            SortOrder::Ascending => self.key.as_str().to_owned(),
+            SortOrder::Descending => format!("-{}", self.key.as_str()),
+        }
+    }
+
+    fn from_url_value(value: &str) -> Option<Self> {
+        match value.strip_prefix('-') {
+            Some(key) => SortKey::parse(key).map(|key| Self { key, order: SortOrder::Descending }),
+            None => SortKey::parse(value).map(|key| Self { key, order: SortOrder::Ascending }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Sort {
+    pub primary: SortBy,
+    /// Tie-breaker for movies with the same primary value.
+    pub secondary: Option<SortBy>,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self {
+            primary: SortBy { key: SortKey::Added, order: SortOrder::Ascending },
+            secondary: None,
+        }
+    }
+}
+
+impl Sort {
+    pub fn from_url_search(url_search: &UrlSearch) -> Self {
+        let mut sort_bys = url_search
+            .get(SORT)
+            .into_iter()
+            .flatten()
+            .filter_map(|value| SortBy::from_url_value(value));
+        match sort_bys.next() {
+            Some(primary) => Self { primary, secondary: sort_bys.next() },
+            None => Self::default(),
+        }
+    }
+
+    pub fn write_url_search(&self, url_search: &mut UrlSearch) {
+        url_search.remove(SORT);
+        if *self == Self::default() {
+            return;
+        }
+        url_search.push_value(SORT, self.primary.to_url_value());
+        if let Some(secondary) = self.secondary {
+            url_search.push_value(SORT, secondary.to_url_value());
+        }
+    }
+
+    /// Ids of the movies in the sort order.
+    /// `MovieId` is the final tie-breaker, so the order is stable across updates.
+    fn sorted_ids<'a>(&self, movies: impl Iterator<Item = (&'a MovieId, &'a Movie)>) -> Vec<MovieId> {
+        let mut rows = movies
+            .map(|(movie_id, movie)| SortRow { movie_id: *movie_id, movie, lowercase_title: movie.title.to_lowercase() })
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| {
+            self.primary
+                .compare(a, b)
+                .then_with(|| self.secondary.map_or(Ordering::Equal, |secondary| secondary.compare(a, b)))
+                .then_with(|| a.movie_id.cmp(&b.movie_id))
+        });
+        rows.into_iter().map(|row| row.movie_id).collect()
+    }
+}
+
+/// A movie with its sort keys computed once per sort, not in every comparison.
+struct SortRow<'a> {
+    movie_id: MovieId,
+    movie: &'a Movie,
+    lowercase_title: String,
+}
+
+// ------ NewMovieForm ------
+
+#[derive(Default)]
+struct NewMovieForm {
+    title: String,
+    year: String,
+    genres: String,
+    runtime: String,
+    rating: String,
+    poster_url: String,
+    synopsis: String,
+
+    errors: FormErrors,
+}
+
+#[derive(Default)]
+struct FormErrors {
+    title: Option<String>,
+    year: Option<String>,
+    runtime: Option<String>,
+    rating: Option<String>,
+}
+
+impl NewMovieForm {
+    fn validate(&self) -> Result<Movie, FormErrors> {
+        let title = parse_title(&self.title);
+        let year = parse_year(&self.year);
+        let runtime = parse_runtime(&self.runtime);
+        let rating = parse_rating(&self.rating);
+
+        match (title, year, runtime, rating) {
+            (Ok(title), Ok(year), Ok(runtime), Ok(rating)) => Ok(Movie {
+                title,
+                year,
+                genres: parse_list(&self.genres),
+                tags: Vec::new(),
+                cast: Vec::new(),
+                crew: Vec::new(),
+                runtime,
+                rating,
+                average_rating: None,
+                review: None,
+                poster_url: non_empty(&self.poster_url),
+                thumbnail_url: None,
+                synopsis: non_empty(&self.synopsis),
+                watch_status: WatchStatus::default(),
+                watch_history: Vec::new(),
+                version: 0,
+            }),
+            (title, year, runtime, rating) => Err(FormErrors {
+                title: title.err(),
+                year: year.err(),
+                runtime: runtime.err(),
+                rating: rating.err(),
+            }),
+        }
+    }
+}
+
+// ------ DuplicateWarning ------
+
+/// A new movie waiting for confirmation, because it looks like a movie already in the collection.
+struct DuplicateWarning {
+    movie: Movie,
+    existing_movie_ids: Vec<MovieId>,
+}
+
+// ------ ImportReview ------
+
+/// Imported movies waiting for confirmation, because some of them look like movies already in the collection.
+struct ImportReview {
+    movies: Vec<Movie>,
+    /// The existing movie each imported movie is likely a duplicate of, in the order of `movies`.
+    duplicates_of: Vec<Option<MovieId>>,
+    /// Indices of the imported movies the user wants to add as new movies instead of merging them.
+    kept_separate: BTreeSet<usize>,
+}
+
+// ------ MovieEdit ------
+
+struct MovieEdit {
+    movie_id: MovieId,
+    field: MovieField,
+    text: String,
+    error: Option<String>,
+    input: ElRef<web_sys::HtmlInputElement>,
+    /// The movie before the edit, so the whole edit is recorded as one change in the history.
+    original_movie: Movie,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MovieField {
+    Title,
+    Year,
+    Genres,
+    Cast,
+    Crew,
+    Runtime,
+    Rating,
+    PosterUrl,
+    Synopsis,
+    Review,
 }
 
-pub struct Movie {
-    name: String,
+impl MovieField {
+    pub const ALL: [Self; 10] = [
+        Self::Title,
+        Self::Year,
+        Self::Genres,
+        Self::Cast,
+        Self::Crew,
+        Self::Runtime,
+        Self::Rating,
+        Self::PosterUrl,
+        Self::Synopsis,
+        Self::Review,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Year => "Year",
+            Self::Genres => "Genres",
+            Self::Cast => "Cast",
+            Self::Crew => "Crew",
+            Self::Runtime => "Runtime",
+            Self::Rating => "Rating",
+            Self::PosterUrl => "Poster URL",
+            Self::Synopsis => "Synopsis",
+            Self::Review => "Review",
+        }
+    }
+
+    pub fn text(self, movie: &Movie) -> String {
+        match self {
+            Self::Title => movie.title.clone(),
+            Self::Year => movie.year.map(|year| year.to_string()).unwrap_or_default(),
+            Self::Genres => movie.genres.join(", "),
+            Self::Cast => movie.cast.join(", "),
+            Self::Crew => movie.crew.join(", "),
+            Self::Runtime => movie.runtime.map(|runtime| runtime.to_string()).unwrap_or_default(),
+            Self::Rating => movie.rating.map(format_stars).unwrap_or_default(),
+            Self::PosterUrl => movie.poster_url.clone().unwrap_or_default(),
+            Self::Synopsis => movie.synopsis.clone().unwrap_or_default(),
+            Self::Review => movie.review.as_ref().map(|review| review.text.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Copies the field value without going through its text representation.
+    pub fn copy(self, from: &Movie, to: &mut Movie) {
+        match self {
+            Self::Title => to.title = from.title.clone(),
+            Self::Year => to.year = from.year,
+            Self::Genres => to.genres = from.genres.clone(),
+            Self::Cast => to.cast = from.cast.clone(),
+            Self::Crew => to.crew = from.crew.clone(),
+            Self::Runtime => to.runtime = from.runtime,
+            Self::Rating => to.rating = from.rating,
+            Self::PosterUrl => {
+                to.poster_url = from.poster_url.clone();
+                to.thumbnail_url = from.thumbnail_url.clone();
+            },
+            Self::Synopsis => to.synopsis = from.synopsis.clone(),
+            Self::Review => to.review = from.review.clone(),
+        }
+    }
+
+    pub fn set(self, movie: &mut Movie, text: &str) -> Result<(), String> {
+        match self {
+            Self::Title => movie.title = parse_title(text)?,
+            Self::Year => movie.year = parse_year(text)?,
+            Self::Genres => movie.genres = parse_list(text),
+            Self::Cast => movie.cast = parse_list(text),
+            Self::Crew => movie.crew = parse_list(text),
+            Self::Runtime => movie.runtime = parse_runtime(text)?,
+            Self::Rating => movie.rating = parse_rating(text)?,
+            Self::PosterUrl => {
+                let poster_url = non_empty(text);
+                // The thumbnail belongs to the uploaded poster.
+                if poster_url != movie.poster_url {
+                    movie.thumbnail_url = None;
+                }
+                movie.poster_url = poster_url;
+            },
+            Self::Synopsis => movie.synopsis = non_empty(text),
+            Self::Review => {
+                let spoiler = movie.review.as_ref().map_or(false, |review| review.spoiler);
+                movie.review = non_empty(text).map(|text| Review { text, spoiler });
+            },
+        }
+        Ok(())
+    }
+}
+
+// ------ parsers ------
+
+/// The year of "Roundhay Garden Scene".
+const FIRST_MOVIE_YEAR: i32 = 1888;
+
+fn parse_title(text: &str) -> Result<String, String> {
+    non_empty(text).ok_or_else(|| "Title is required.".to_owned())
+}
+
+fn parse_year(text: &str) -> Result<Option<i32>, String> {
+    let max_year = Local::now().year() + 10;
+    match parse_optional::<i32>(text) {
+        Ok(Some(year)) if year < FIRST_MOVIE_YEAR || year > max_year => {
+            Err(format!("Year has to be between {} and {}.", FIRST_MOVIE_YEAR, max_year))
+        },
+        Ok(year) => Ok(year),
+        Err(_) => Err("Year has to be a number.".to_owned()),
+    }
+}
+
+/// Comma-separated genres or names.
+fn parse_list(text: &str) -> Vec<String> {
+    text
+        .split(',')
+        .map(str::trim)
+        .filter(|item| not(item.is_empty()))
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+fn parse_runtime(text: &str) -> Result<Option<u32>, String> {
+    match parse_optional::<u32>(text) {
+        Ok(Some(0)) | Err(_) => Err("Runtime has to be a positive number of minutes.".to_owned()),
+        Ok(runtime) => Ok(runtime),
+    }
+}
+
+fn parse_rating(text: &str) -> Result<Option<u8>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    rating::parse_stars(text)
+        .map(Some)
+        .ok_or_else(|| "Rating has to be between 0.5 and 5 stars, in half-star steps.".to_owned())
+}
+
+fn parse_optional<T: std::str::FromStr>(text: &str) -> Result<Option<T>, T::Err> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    text.parse().map(Some)
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() { None } else { Some(text.to_owned()) }
 }
 
 // ------ ------
@@ -53,27 +838,1243 @@ pub struct Movie {
 
 pub enum Msg {
     MoviesFetched(fetch::Result<BTreeMap<MovieId, Movie>>),
-    ChangesSaved(Option<FetchError>),
+    ListsFetched(fetch::Result<BTreeMap<ListId, List>>),
+    ChangesSaved(sync::Request, fetch::Result<sync::NewVersion>, Rollback),
+    ServerMovieFetched(MovieId, fetch::Result<Movie>),
+    ConflictMsg(conflict::Msg),
     ClearErrors,
-    
+    CacheMovies,
+
+    WentOnline,
+    WentOffline,
+    ReplaySyncQueue,
+    QueuedRequestSent(fetch::Result<sync::NewVersion>),
+
+    NewMovieTitleChanged(String),
+    NewMovieYearChanged(String),
+    NewMovieGenresChanged(String),
+    NewMovieRuntimeChanged(String),
+    NewMovieRatingChanged(String),
+    NewMoviePosterUrlChanged(String),
+    NewMovieSynopsisChanged(String),
+
+    MoviesScrolled(f64),
+    MeasureViewport,
+    PosterLoaded(String),
+
+    SearchChanged(String),
+    GenreFilterChanged(String),
+    YearFromFilterChanged(String),
+    YearToFilterChanged(String),
+    WatchStatusFilterChanged(String),
+    ClearFilters,
+
+    SortKeyChanged(String),
+    SortOrderToggled,
+    SecondarySortKeyChanged(String),
+    SecondarySortOrderToggled,
+
+    StartMovieEdit(MovieId, MovieField),
+    MovieEditChanged(String),
+    StopMovieEdit,
+    CancelMovieEdit,
+    SaveMovieEdits,
+
+    OpenImport,
+    ImportMsg(import::Msg),
+    ToggleImportMerge(usize),
+    ConfirmImport,
+    CancelImport,
+
+    ToggleExportMenu,
+    Export(ExportFormat),
+
+    FindDuplicates,
+    CloseDuplicatesReport,
+    StartMerge(MovieId, MovieId),
+    MergeMsg(merge::Msg),
+
+    OpenPicker,
+    PickerMsg(picker::Msg),
+
     AddMovie,
+    AddMovieAnyway,
+    DismissDuplicateWarning,
+    ChangeWatchStatus(MovieId, WatchStatus),
+    RateMovie(MovieId, u8),
+    ToggleReviewSpoiler(MovieId),
     DeleteMovie(MovieId),
+
+    Undo,
+    Redo,
+    HideUndoToast,
+
+    MovieSelectionToggled(MovieId, bool),
+    SelectAllFiltered,
+    ClearSelection,
+    BulkTagChanged(String),
+    BulkAddTag,
+    BulkListChanged(String),
+    BulkAddToList,
+    BulkMarkWatched,
+    BulkDelete,
 }
 
-pub fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::MoviesFetched(Ok(movies)) => {},
-        Msg::MoviesFetched(Err(fetch_error)) => {},
+        Msg::MoviesFetched(Ok(mut movies)) => {
+            // The server doesn't know about the queued changes yet.
+            model.sync_queue.apply(&mut movies);
+            // Nor about the edits waiting for the debounced save.
+            if let RemoteData::Loaded(local_movies) = &model.clients {
+                for movie_id in model.unsaved_movies.keys() {
+                    if let Some(movie) = local_movies.get(movie_id) {
+                        movies.insert(*movie_id, movie.clone());
+                    }
+                }
+            }
+            model.clients = RemoteData::Loaded(movies);
+            movies_changed(model, orders);
+            orders.after_next_render(|_| Msg::MeasureViewport);
+        },
+        Msg::MoviesFetched(Err(fetch_error)) => {
+            if let RemoteData::Loading = model.clients {
+                model.clients = RemoteData::NotAsked;
+                model.errors.push(format!("{:?}", fetch_error));
+            } else {
+                // Keep cached movies, but don't pretend they are up to date.
+                model.errors.push(format!("Cannot refresh movies, showing cached ones: {:?}", fetch_error));
+            }
+        },
+
+        Msg::ListsFetched(Ok(lists)) => model.lists = lists,
+        // The "Add to list" action is hidden until the lists are loaded.
+        Msg::ListsFetched(Err(fetch_error)) => model.errors.push(format!("Cannot load lists: {:?}", fetch_error)),
+
+        Msg::ChangesSaved(request, result, rollback) => {
+            model.changes_status = request_finished(&model.changes_status, result.is_ok());
+            let versioned_movie_id = request.versioned_movie_id();
+            match (result, request) {
+                (Ok(new_version), _) => set_movie_version(model, new_version, orders),
+                // Keep the change and send it again later.
+                (Err(fetch_error), request) if sync::is_network_error(&fetch_error) => {
+                    model.sync_queue.push(request);
+                    schedule_replay(model, orders);
+                },
+                (Err(fetch_error), sync::Request::Update(movie_id, _)) if sync::is_conflict(&fetch_error) => {
+                    fetch_server_movie(model, movie_id, orders);
+                },
+                // The merge hasn't happened; restore both movies and resolve the surviving one.
+                // The user can merge them again afterwards.
+                (Err(fetch_error), sync::Request::Merge { surviving_id, .. }) if sync::is_conflict(&fetch_error) => {
+                    if let RemoteData::Loaded(movies) = &mut model.clients {
+                        rollback.apply(movies);
+                    }
+                    movies_changed(model, orders);
+                    fetch_server_movie(model, surviving_id, orders);
+                },
+                (Err(fetch_error), _) => {
+                    model.errors.push(format!("{:?}", fetch_error));
+                    rollback.apply_to_lists(&mut model.lists);
+                    if let RemoteData::Loaded(movies) = &mut model.clients {
+                        rollback.apply(movies);
+                    }
+                    movies_changed(model, orders);
+                    // The rolled back movies don't match the recorded changes anymore.
+                    model.history.clear();
+                },
+            }
+            if let Some(movie_id) = versioned_movie_id {
+                model.saving_movies.remove(&movie_id);
+                // Send changes made while the request was in flight.
+                if model.unsaved_movies.contains_key(&movie_id) && model.save_edits_handle.is_none() {
+                    orders.send_msg(Msg::SaveMovieEdits);
+                }
+            }
+        },
+
+        // ------ Conflicts ------
+
+        Msg::ServerMovieFetched(movie_id, Ok(theirs)) => {
+            let movies = match &mut model.clients {
+                RemoteData::Loaded(movies) => movies,
+                _ => return,
+            };
+            // The movie may have been deleted while its server version was being fetched.
+            let mine = match movies.get(&movie_id) {
+                Some(movie) => movie.clone(),
+                None => return,
+            };
+            // A newer server movie replaces the one in the open dialog.
+            model.conflicts.retain(|conflict| conflict.movie_id() != movie_id);
+            // `average_rating` isn't cached, so it's ignored in the comparison.
+            if Movie { version: theirs.version, average_rating: theirs.average_rating, ..mine.clone() } == theirs {
+                movies.insert(movie_id, theirs);
+                movies_changed(model, orders);
+                return;
+            }
+            model.conflicts.push_back(conflict::Model::new(movie_id, mine, theirs));
+        },
+        Msg::ServerMovieFetched(_, Err(fetch_error)) => model.errors.push(format!("{:?}", fetch_error)),
+        Msg::ConflictMsg(conflict::Msg::KeepMine) => {
+            if let Some(conflict) = model.conflicts.pop_front() {
+                let movie = conflict.kept_movie();
+                resolve_conflict(model, &conflict, movie, orders);
+            }
+        },
+        Msg::ConflictMsg(conflict::Msg::TakeTheirs) => {
+            if let Some(conflict) = model.conflicts.pop_front() {
+                let movie = conflict.theirs().clone();
+                resolve_conflict(model, &conflict, movie, orders);
+            }
+        },
+        Msg::ConflictMsg(conflict::Msg::SaveMerged) => {
+            if let Some(conflict) = model.conflicts.pop_front() {
+                let movie = conflict.resolved_movie();
+                resolve_conflict(model, &conflict, movie, orders);
+            }
+        },
+        Msg::ConflictMsg(msg) => {
+            if let Some(conflict) = model.conflicts.front_mut() {
+                conflict::update(msg, conflict, &mut orders.proxy(Msg::ConflictMsg));
+            }
+        },
+
+        Msg::ClearErrors => model.errors.clear(),
+        Msg::CacheMovies => {
+            model.cache_movies_handle = None;
+            if let RemoteData::Loaded(movies) = &model.clients {
+                sync::cache_movies(movies);
+            }
+        },
+
+        // ------ SyncQueue ------
+
+        Msg::WentOnline => {
+            model.sync_queue.offline = false;
+            model.sync_queue.reset_retry_delay();
+            model.replay_handle = None;
+            replay_sync_queue(model, orders);
+        },
+        Msg::WentOffline => model.sync_queue.offline = true,
+        Msg::ReplaySyncQueue => {
+            model.replay_handle = None;
+            replay_sync_queue(model, orders);
+        },
+        Msg::QueuedRequestSent(result) => {
+            model.changes_status = request_finished(&model.changes_status, result.is_ok());
+            model.sync_queue.replaying = false;
+            match result {
+                Err(fetch_error) if sync::is_network_error(&fetch_error) => schedule_replay(model, orders),
+                Err(fetch_error) => {
+                    model.sync_queue.reset_retry_delay();
+                    // The server has refused the change; drop it so it doesn't block the following ones.
+                    match model.sync_queue.pop_front() {
+                        Some(sync::Request::Update(movie_id, _)) if sync::is_conflict(&fetch_error) => {
+                            fetch_server_movie(model, movie_id, orders);
+                        },
+                        refused_request => {
+                            model.errors.push(format!("{:?}", fetch_error));
+                            // The refused change is still displayed; replace the movies with the server ones
+                            // and the remaining queued changes. The recorded changes may contain it too.
+                            model.history.clear();
+                            orders.perform_cmd(async { Msg::MoviesFetched(request_movies().await) });
+                            if let Some(sync::Request::AddToList(..)) = refused_request {
+                                orders.perform_cmd(async { Msg::ListsFetched(lists::request_lists().await) });
+                            }
+                        },
+                    }
+                    replay_sync_queue(model, orders);
+                },
+                Ok(new_version) => {
+                    model.sync_queue.reset_retry_delay();
+                    model.sync_queue.pop_front();
+                    set_movie_version(model, new_version, orders);
+                    replay_sync_queue(model, orders);
+                },
+            }
+        },
+
+        // ------ NewMovieForm ------
+
+        Msg::NewMovieTitleChanged(title) => model.new_movie_form.title = title,
+        Msg::NewMovieYearChanged(year) => model.new_movie_form.year = year,
+        Msg::NewMovieGenresChanged(genres) => model.new_movie_form.genres = genres,
+        Msg::NewMovieRuntimeChanged(runtime) => model.new_movie_form.runtime = runtime,
+        Msg::NewMovieRatingChanged(rating) => model.new_movie_form.rating = rating,
+        Msg::NewMoviePosterUrlChanged(poster_url) => model.new_movie_form.poster_url = poster_url,
+        Msg::NewMovieSynopsisChanged(synopsis) => model.new_movie_form.synopsis = synopsis,
+
+        // ------ VirtualList ------
 
-        Msg::ChangesSaved(None) => {},
-        Msg::ChangesSaved(Some(fetch_error)) => {},
+        Msg::MoviesScrolled(scroll_top) => model.virtual_list.scroll_top = scroll_top,
+        Msg::PosterLoaded(poster_url) => {
+            if not(model.loaded_posters.insert(poster_url)) {
+                orders.skip();
+            }
+        },
+        Msg::MeasureViewport => {
+            if let Some(viewport) = model.viewport.get() {
+                model.virtual_list.viewport_height = f64::from(viewport.client_height());
+            } else {
+                orders.skip();
+            }
+        },
 
-        Msg::ClearErrors => {},
+        // ------ Filters ------
+
+        Msg::SearchChanged(search) => {
+            model.filters.search = search;
+            url_search_changed(model);
+        },
+        Msg::GenreFilterChanged(genre) => {
+            model.filters.genre = non_empty(&genre);
+            url_search_changed(model);
+        },
+        Msg::YearFromFilterChanged(year) => {
+            model.filters.year_from = year.trim().parse().ok();
+            url_search_changed(model);
+        },
+        Msg::YearToFilterChanged(year) => {
+            model.filters.year_to = year.trim().parse().ok();
+            url_search_changed(model);
+        },
+        Msg::WatchStatusFilterChanged(watch_status) => {
+            model.filters.watch_status = WatchStatus::parse(&watch_status);
+            url_search_changed(model);
+        },
+        Msg::ClearFilters => {
+            model.filters = Filters::default();
+            url_search_changed(model);
+        },
+
+        // ------ Sort ------
+
+        Msg::SortKeyChanged(key) => {
+            if let Some(key) = SortKey::parse(&key) {
+                model.sort.primary.key = key;
+                url_search_changed(model);
+            }
+        },
+        Msg::SortOrderToggled => {
+            model.sort.primary.order = model.sort.primary.order.toggled();
+            url_search_changed(model);
+        },
+        Msg::SecondarySortKeyChanged(key) => {
+            model.sort.secondary = SortKey::parse(&key).map(|key| SortBy {
+                key,
+                order: model.sort.secondary.map_or(SortOrder::Ascending, |secondary| secondary.order),
+            });
+            url_search_changed(model);
+        },
+        Msg::SecondarySortOrderToggled => {
+            if let Some(secondary) = &mut model.sort.secondary {
+                secondary.order = secondary.order.toggled();
+                url_search_changed(model);
+            }
+        },
+
+        // ------ MovieEdit ------
+
+        Msg::StartMovieEdit(movie_id, field) => {
+            stop_movie_edit(model, orders);
+            let movie = match &model.clients {
+                RemoteData::Loaded(movies) => movies.get(&movie_id),
+                _ => None,
+            };
+            if let Some(movie) = movie {
+                let input = ElRef::default();
+                orders.after_next_render({
+                    let input = input.clone();
+                    move |_| {
+                        // The row may be out of the rendered window already.
+                        if let Some(input) = input.get() {
+                            let _ = input.focus();
+                        }
+                    }
+                });
+                model.movie_edit = Some(MovieEdit {
+                    movie_id,
+                    field,
+                    text: field.text(movie),
+                    error: None,
+                    input,
+                    original_movie: movie.clone(),
+                });
+            }
+        },
+        Msg::MovieEditChanged(text) => {
+            let edit = match &mut model.movie_edit {
+                Some(edit) => edit,
+                None => return,
+            };
+            edit.text = text;
+            let movie = match &mut model.clients {
+                RemoteData::Loaded(movies) => movies.get_mut(&edit.movie_id),
+                _ => None,
+            };
+            if let Some(movie) = movie {
+                let saved_movie = movie.clone();
+                match edit.field.set(movie, &edit.text) {
+                    Ok(()) => {
+                        edit.error = None;
+                        model.unsaved_movies.entry(edit.movie_id).or_insert(saved_movie);
+                        // Replacing the handle aborts the previous timeout.
+                        model.save_edits_handle = Some(orders.perform_cmd_with_handle(
+                            cmds::timeout(SAVE_EDITS_DEBOUNCE_MS, || Msg::SaveMovieEdits)
+                        ));
+                        // The edited value may move the movie in the list; it's cached with the save.
+                        refresh_visible_movies(model);
+                    },
+                    Err(error) => edit.error = Some(error),
+                }
+            }
+        },
+        Msg::StopMovieEdit => {
+            stop_movie_edit(model, orders);
+            save_movie_edits(model, orders);
+        },
+        Msg::CancelMovieEdit => {
+            if let Some(edit) = model.movie_edit.take() {
+                revert_movie_edit(model, edit, orders);
+            }
+        },
+        Msg::SaveMovieEdits => save_movie_edits(model, orders),
+
+        // ------ Import ------
+
+        Msg::OpenImport => model.import = Some(import::Model::default()),
+        Msg::ImportMsg(import::Msg::Close) => model.import = None,
+        Msg::ImportMsg(import::Msg::Import) => {
+            if let Some(import) = model.import.take() {
+                let movies = match &model.clients {
+                    RemoteData::Loaded(movies) => movies,
+                    _ => return,
+                };
+                let imported_movies = import.valid_movies();
+                let duplicates_of = duplicates::find_first_duplicates(&imported_movies, movies);
+                if duplicates_of.iter().all(Option::is_none) {
+                    import_movies(model, imported_movies, duplicates_of, orders);
+                } else {
+                    model.import_review = Some(ImportReview {
+                        movies: imported_movies,
+                        duplicates_of,
+                        kept_separate: BTreeSet::new(),
+                    });
+                }
+            }
+        },
+        Msg::ImportMsg(msg) => {
+            if let Some(import) = &mut model.import {
+                import::update(msg, import, &mut orders.proxy(Msg::ImportMsg));
+            }
+        },
+        Msg::ToggleImportMerge(index) => {
+            if let Some(review) = &mut model.import_review {
+                if not(review.kept_separate.remove(&index)) {
+                    review.kept_separate.insert(index);
+                }
+            }
+        },
+        Msg::ConfirmImport => {
+            if let Some(ImportReview { movies, mut duplicates_of, kept_separate }) = model.import_review.take() {
+                for index in kept_separate {
+                    duplicates_of[index] = None;
+                }
+                import_movies(model, movies, duplicates_of, orders);
+            }
+        },
+        Msg::CancelImport => model.import_review = None,
+
+        // ------ Export ------
+
+        Msg::ToggleExportMenu => model.export_menu_visible = not(model.export_menu_visible),
+        Msg::Export(format) => {
+            model.export_menu_visible = false;
+            let movies = match &model.clients {
+                RemoteData::Loaded(movies) => movies,
+                _ => return,
+            };
+            let visible_movies = visible_movies(movies, &model.visible_movie_ids);
+            let result = format
+                .export(&visible_movies)
+                .and_then(|content| {
+                    download(format.file_name(), format.mime_type(), &content)
+                        .map_err(|error| format!("{:?}", error))
+                });
+            if let Err(error) = result {
+                model.errors.push(format!("Export failed: {}", error));
+            }
+        },
+
+        // ------ Duplicates ------
+
+        Msg::FindDuplicates => {
+            if let RemoteData::Loaded(movies) = &model.clients {
+                model.duplicate_groups = Some(duplicates::find_duplicate_groups(movies));
+            }
+        },
+        Msg::CloseDuplicatesReport => model.duplicate_groups = None,
+        Msg::StartMerge(a, b) => {
+            if let RemoteData::Loaded(movies) = &model.clients {
+                if let (Some(movie_a), Some(movie_b)) = (movies.get(&a), movies.get(&b)) {
+                    model.merge = Some(merge::Model::new((a, movie_a.clone()), (b, movie_b.clone())));
+                }
+            }
+        },
+        Msg::MergeMsg(merge::Msg::Close) => model.merge = None,
+        Msg::MergeMsg(merge::Msg::Merge) => {
+            if let Some(merge) = model.merge.take() {
+                merge_movies(model, &merge, orders);
+            }
+        },
+        Msg::MergeMsg(msg) => {
+            if let Some(merge) = &mut model.merge {
+                merge::update(msg, merge, &mut orders.proxy(Msg::MergeMsg));
+            }
+        },
+
+        // ------ Picker ------
+
+        Msg::OpenPicker => {
+            // `Math.random` returns 53 random bits at most.
+            let seed = (js_sys::Math::random() * (1_u64 << 53) as f64) as u64;
+            model.picker = Some(picker::init(seed));
+        },
+        Msg::PickerMsg(picker::Msg::Close) => model.picker = None,
+        Msg::PickerMsg(msg) => {
+            if let Some(picker) = &mut model.picker {
+                picker::update(msg, picker, &mut orders.proxy(Msg::PickerMsg));
+            }
+        },
 
         // ------ Client ------
 
-        Msg::AddMovie => {},
-        Msg::DeleteMovie(movie_id) => {},
+        Msg::AddMovie => {
+            let movies = match &model.clients {
+                RemoteData::Loaded(movies) => movies,
+                _ => return,
+            };
+            let movie = match model.new_movie_form.validate() {
+                Ok(movie) => movie,
+                Err(errors) => {
+                    model.new_movie_form.errors = errors;
+                    return;
+                }
+            };
+            let existing_movie_ids = duplicates::find_duplicates_of(&movie, movies);
+            if existing_movie_ids.is_empty() {
+                add_movie(model, movie, orders);
+            } else {
+                model.duplicate_warning = Some(DuplicateWarning { movie, existing_movie_ids });
+            }
+        },
+        Msg::AddMovieAnyway => {
+            if let Some(DuplicateWarning { movie, .. }) = model.duplicate_warning.take() {
+                add_movie(model, movie, orders);
+            }
+        },
+        Msg::DismissDuplicateWarning => model.duplicate_warning = None,
+        Msg::ChangeWatchStatus(movie_id, watch_status) => {
+            let before = snapshot(model, &[movie_id]);
+            let movie = match &mut model.clients {
+                RemoteData::Loaded(movies) => movies.get_mut(&movie_id),
+                _ => None,
+            };
+            if let Some(movie) = movie {
+                let saved_movie = movie.clone();
+                if movie.change_watch_status(watch_status, Local::today().naive_local()) {
+                    model.unsaved_movies.entry(movie_id).or_insert(saved_movie);
+                    save_movie_edits(model, orders);
+                    record_change(model, format!("Mark as {}", watch_status.label().to_lowercase()), before, orders);
+                }
+            }
+        },
+        Msg::RateMovie(movie_id, rating) => {
+            let before = snapshot(model, &[movie_id]);
+            let movie = match &mut model.clients {
+                RemoteData::Loaded(movies) => movies.get_mut(&movie_id),
+                _ => None,
+            };
+            if let Some(movie) = movie {
+                let previous_rating = movie.rating;
+                // Clicking the current rating again clears it.
+                movie.rating = if previous_rating == Some(rating) { None } else { Some(rating) };
+                let movie = movie.clone();
+
+                model.unsaved_movies.remove(&movie_id);
+                let rollback = Rollback::RestoreRating(movie_id, previous_rating);
+                send_requests(model, vec![(sync::Request::Update(movie_id, movie), rollback)], orders);
+                record_change(model, "Rate movie", before, orders);
+            }
+        },
+        Msg::ToggleReviewSpoiler(movie_id) => {
+            let before = snapshot(model, &[movie_id]);
+            let movie = match &mut model.clients {
+                RemoteData::Loaded(movies) => movies.get_mut(&movie_id),
+                _ => None,
+            };
+            if let Some(movie) = movie.filter(|movie| movie.review.is_some()) {
+                let saved_movie = movie.clone();
+                if let Some(review) = &mut movie.review {
+                    review.spoiler = not(review.spoiler);
+                }
+                model.unsaved_movies.entry(movie_id).or_insert(saved_movie);
+                save_movie_edits(model, orders);
+                record_change(model, "Toggle spoiler flag", before, orders);
+            }
+        },
+        Msg::DeleteMovie(movie_id) => {
+            let movie = match &mut model.clients {
+                RemoteData::Loaded(movies) => movies.remove(&movie_id),
+                _ => None,
+            };
+            if let Some(movie) = movie {
+                model.unsaved_movies.remove(&movie_id);
+                model.selected_movies.remove(&movie_id);
+                let label = format!("Delete \"{}\"", movie.title);
+                record_change(model, label, vec![(movie_id, Some(movie.clone()))], orders);
+                let rollback = Rollback::RestoreMovie(movie_id, movie);
+                send_requests(model, vec![(sync::Request::Delete(movie_id), rollback)], orders);
+            }
+        },
+
+        // ------ History ------
+
+        Msg::Undo => {
+            stop_movie_edit(model, orders);
+            if let Some(steps) = model.history.undo() {
+                apply_history_steps(model, steps, orders);
+                show_undo_toast(model, orders);
+            }
+        },
+        Msg::Redo => {
+            stop_movie_edit(model, orders);
+            if let Some(steps) = model.history.redo() {
+                apply_history_steps(model, steps, orders);
+                show_undo_toast(model, orders);
+            }
+        },
+        Msg::HideUndoToast => model.undo_toast_handle = None,
+
+        // ------ Selection ------
+
+        Msg::MovieSelectionToggled(movie_id, shift_key) => {
+            let select = not(model.selected_movies.contains(&movie_id));
+            let mut movie_ids = vec![movie_id];
+            if let Some(anchor) = model.selection_anchor.filter(|_| shift_key) {
+                let visible_ids = &model.visible_movie_ids;
+                let position = |movie_id: MovieId| visible_ids.iter().position(|id| *id == movie_id);
+                if let (Some(from), Some(to)) = (position(anchor), position(movie_id)) {
+                    movie_ids = visible_ids[from.min(to)..=from.max(to)].to_vec();
+                }
+            }
+            for movie_id in movie_ids {
+                if select {
+                    model.selected_movies.insert(movie_id);
+                } else {
+                    model.selected_movies.remove(&movie_id);
+                }
+            }
+            model.selection_anchor = Some(movie_id);
+        },
+        Msg::SelectAllFiltered => model.selected_movies.extend(model.visible_movie_ids.iter().copied()),
+        Msg::ClearSelection => {
+            model.selected_movies.clear();
+            model.selection_anchor = None;
+        },
+        Msg::BulkTagChanged(tag) => model.bulk_tag = tag,
+        Msg::BulkAddTag => {
+            let tag = model.bulk_tag.trim().to_owned();
+            if tag.is_empty() {
+                return;
+            }
+            change_selected_movies(model, &format!("Tag with \"{}\"", tag), orders, |movie| {
+                if movie.tags.contains(&tag) {
+                    return false;
+                }
+                movie.tags.push(tag.clone());
+                true
+            });
+            model.bulk_tag.clear();
+        },
+        Msg::BulkListChanged(list_id) => model.bulk_list = ListId::from_string(&list_id).ok(),
+        Msg::BulkAddToList => {
+            let list_id = match model.bulk_list {
+                Some(list_id) => list_id,
+                None => return,
+            };
+            let list = match model.lists.get_mut(&list_id) {
+                Some(list) => list,
+                None => return,
+            };
+            let movie_ids = model
+                .selected_movies
+                .iter()
+                .filter(|movie_id| not(list.movie_ids.contains(movie_id)))
+                .copied()
+                .collect::<Vec<_>>();
+            if movie_ids.is_empty() {
+                return;
+            }
+            list.movie_ids.extend(&movie_ids);
+            let rollback = Rollback::RemoveFromList(list_id, movie_ids.clone());
+            send_requests(model, vec![(sync::Request::AddToList(list_id, movie_ids), rollback)], orders);
+        },
+        Msg::BulkMarkWatched => {
+            let today = Local::today().naive_local();
+            change_selected_movies(model, "Mark as watched", orders, |movie| {
+                // Already watched movies would get a rewatch.
+                movie.watch_status != WatchStatus::Watched
+                    && movie.change_watch_status(WatchStatus::Watched, today)
+            });
+        },
+        Msg::BulkDelete => {
+            let movies = match &mut model.clients {
+                RemoteData::Loaded(movies) => movies,
+                _ => return,
+            };
+            let question = format!("Delete {} movies?", model.selected_movies.len());
+            if not(window().confirm_with_message(&question).unwrap_or_default()) {
+                return;
+            }
+            let mut before = Vec::new();
+            let mut requests = Vec::new();
+            for movie_id in mem::take(&mut model.selected_movies) {
+                if let Some(movie) = movies.remove(&movie_id) {
+                    before.push((movie_id, Some(movie.clone())));
+                    model.unsaved_movies.remove(&movie_id);
+                    requests.push((sync::Request::Delete(movie_id), Rollback::RestoreMovie(movie_id, movie)));
+                }
+            }
+            model.selection_anchor = None;
+            send_requests(model, requests, orders);
+            record_change(model, format!("Delete {} movies", before.len()), before, orders);
+        },
+    }
+}
+
+fn add_movie(model: &mut Model, movie: Movie, orders: &mut impl Orders<Msg>) {
+    let movies = match &mut model.clients {
+        RemoteData::Loaded(movies) => movies,
+        _ => return,
+    };
+    let movie_id = MovieId::new();
+    movies.insert(movie_id, movie.clone());
+    model.new_movie_form = NewMovieForm::default();
+    model.duplicate_warning = None;
+    record_change(model, format!("Add \"{}\"", movie.title), vec![(movie_id, None)], orders);
+    let rollback = Rollback::RemoveMovies(vec![movie_id]);
+    send_requests(model, vec![(sync::Request::Create(movie_id, movie), rollback)], orders);
+}
+
+/// Applies `change` to every selected movie and saves the changed ones, one request per movie,
+/// so a failed request is reported in `Model.errors` and rolls back only its own movie.
+/// `change` returns `false` if it hasn't changed the movie.
+fn change_selected_movies(
+    model: &mut Model,
+    label: &str,
+    orders: &mut impl Orders<Msg>,
+    mut change: impl FnMut(&mut Movie) -> bool,
+) {
+    let movies = match &mut model.clients {
+        RemoteData::Loaded(movies) => movies,
+        _ => return,
+    };
+    let mut before = Vec::new();
+    let mut requests = Vec::new();
+    for movie_id in &model.selected_movies {
+        let movie = match movies.get_mut(movie_id) {
+            Some(movie) => movie,
+            None => continue,
+        };
+        let original_movie = movie.clone();
+        if not(change(movie)) {
+            continue;
+        }
+        let (movie_id, movie) = (*movie_id, movie.clone());
+        before.push((movie_id, Some(original_movie.clone())));
+        model.unsaved_movies.remove(&movie_id);
+        requests.push((sync::Request::Update(movie_id, movie), Rollback::RestoreMovie(movie_id, original_movie)));
+    }
+    send_requests(model, requests, orders);
+    record_change(model, label, before, orders);
+}
+
+/// Creates the movies with fresh ids in one request and removes them again if it fails.
+/// Returns the new ids.
+fn add_movies(model: &mut Model, new_movies: Vec<Movie>, orders: &mut impl Orders<Msg>) -> Vec<MovieId> {
+    let movies = match &mut model.clients {
+        RemoteData::Loaded(movies) => movies,
+        _ => return Vec::new(),
+    };
+    if new_movies.is_empty() {
+        return Vec::new();
+    }
+    let new_movies = new_movies
+        .into_iter()
+        .map(|movie| (MovieId::new(), movie))
+        .collect::<Vec<_>>();
+    let movie_ids = new_movies.iter().map(|(movie_id, _)| *movie_id).collect::<Vec<_>>();
+    movies.extend(new_movies.iter().cloned());
+
+    let rollback = Rollback::RemoveMovies(movie_ids.clone());
+    send_requests(model, vec![(sync::Request::CreateMany(new_movies), rollback)], orders);
+    movie_ids
+}
+
+/// Imported movies are merged into the existing movies confirmed in `merge_into` (in the order of `imported_movies`)
+/// instead of being added again.
+/// Repeated movies in the import itself - e.g. rewatches in a Letterboxd diary - are merged together.
+fn import_movies(
+    model: &mut Model,
+    imported_movies: Vec<Movie>,
+    merge_into: Vec<Option<MovieId>>,
+    orders: &mut impl Orders<Msg>,
+) {
+    let movies = match &mut model.clients {
+        RemoteData::Loaded(movies) => movies,
+        _ => return,
+    };
+    let imported_count = imported_movies.len();
+    let mut new_movies = Vec::<Movie>::new();
+    // Original versions of the merged movies, to roll back failed saves.
+    let mut merged_movies = BTreeMap::new();
+
+    for (imported_movie, merge_into) in imported_movies.into_iter().zip(merge_into) {
+        let existing_movie = merge_into.and_then(|movie_id| movies.get_mut(&movie_id).map(|movie| (movie_id, movie)));
+        if let Some((movie_id, movie)) = existing_movie {
+            let original_movie = movie.clone();
+            if movie.merge(imported_movie) {
+                merged_movies.entry(movie_id).or_insert(original_movie);
+            }
+        } else if let Some(movie) = new_movies.iter_mut().find(|movie| is_likely_duplicate(movie, &imported_movie)) {
+            movie.merge(imported_movie);
+        } else {
+            new_movies.push(imported_movie);
+        }
+    }
+
+    let mut before = Vec::new();
+    let mut requests = Vec::new();
+    for (movie_id, original_movie) in merged_movies {
+        let movie = movies[&movie_id].clone();
+        before.push((movie_id, Some(original_movie.clone())));
+        requests.push((sync::Request::Update(movie_id, movie), Rollback::RestoreMovie(movie_id, original_movie)));
+    }
+    send_requests(model, requests, orders);
+    let new_movie_ids = add_movies(model, new_movies, orders);
+    before.extend(new_movie_ids.into_iter().map(|movie_id| (movie_id, None)));
+    record_change(model, format!("Import {} movies", imported_count), before, orders);
+}
+
+fn merge_movies(model: &mut Model, merge: &merge::Model, orders: &mut impl Orders<Msg>) {
+    let movies = match &mut model.clients {
+        RemoteData::Loaded(movies) => movies,
+        _ => return,
+    };
+    let (surviving_id, merged_id) = (merge.surviving_id(), merge.merged_id());
+    let movie = merge.merged_movie();
+    let original_movies = [surviving_id, merged_id]
+        .iter()
+        .filter_map(|movie_id| movies.get(movie_id).map(|movie| (*movie_id, movie.clone())))
+        .collect::<Vec<_>>();
+
+    movies.insert(surviving_id, movie.clone());
+    movies.remove(&merged_id);
+    model.unsaved_movies.remove(&merged_id);
+    model.selected_movies.remove(&merged_id);
+    if let Some(groups) = &mut model.duplicate_groups {
+        for group in groups.iter_mut() {
+            group.retain(|movie_id| *movie_id != merged_id);
+        }
+        groups.retain(|group| group.len() > 1);
+    }
+
+    let request = sync::Request::Merge { surviving_id, merged_id, movie };
+    send_requests(model, vec![(request, Rollback::RestoreMovies(original_movies))], orders);
+    // Merges can't be undone - the server moves the merged movie's list memberships to the surviving movie.
+    // The recorded changes may contain the merged movie, so undoing them would recreate it without its lists.
+    model.history.clear();
+}
+
+// ------ History ------
+
+/// The current state of the given movies, to record a change in the history.
+fn snapshot(model: &Model, movie_ids: &[MovieId]) -> Vec<(MovieId, Option<Movie>)> {
+    match &model.clients {
+        RemoteData::Loaded(movies) => movie_ids
+            .iter()
+            .map(|movie_id| (*movie_id, movies.get(movie_id).cloned()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Call after the change; `before` is the `snapshot` taken before it.
+fn record_change(
+    model: &mut Model,
+    label: impl Into<String>,
+    before: Vec<(MovieId, Option<Movie>)>,
+    orders: &mut impl Orders<Msg>,
+) {
+    if let RemoteData::Loaded(movies) = &model.clients {
+        if model.history.record(label, before, movies) {
+            show_undo_toast(model, orders);
+        }
+    }
+}
+
+/// Records the whole edit, from its start to now, as one change.
+fn stop_movie_edit(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if let Some(edit) = model.movie_edit.take() {
+        let label = format!("Edit {}", edit.field.label().to_lowercase());
+        record_change(model, label, vec![(edit.movie_id, Some(edit.original_movie))], orders);
+    }
+}
+
+/// Restores the movie from before the edit. Parts of the edit saved by the debounced autosave are reverted
+/// on the server too.
+fn revert_movie_edit(model: &mut Model, edit: MovieEdit, orders: &mut impl Orders<Msg>) {
+    let movie = match &mut model.clients {
+        RemoteData::Loaded(movies) => movies.get_mut(&edit.movie_id),
+        _ => None,
+    };
+    let movie = match movie {
+        Some(movie) => movie,
+        None => return,
+    };
+    let mut original_movie = edit.original_movie;
+    // Autosaves during the edit have changed the version.
+    original_movie.version = movie.version;
+    let edited_movie = mem::replace(movie, original_movie.clone());
+
+    let nothing_sent = model.unsaved_movies.get(&edit.movie_id).map_or(false, |saved_movie| {
+        Movie { version: original_movie.version, ..saved_movie.clone() } == original_movie
+    });
+    if nothing_sent {
+        model.unsaved_movies.remove(&edit.movie_id);
+        refresh_visible_movies(model);
+    } else {
+        model.unsaved_movies.entry(edit.movie_id).or_insert(edited_movie);
+        save_movie_edits(model, orders);
+    }
+}
+
+fn show_undo_toast(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    // Replacing the handle aborts the previous timeout.
+    model.undo_toast_handle = Some(orders.perform_cmd_with_handle(
+        cmds::timeout(UNDO_TOAST_MS, || Msg::HideUndoToast)
+    ));
+}
+
+/// Applies undone or redone steps and sends the compensating requests - one per movie,
+/// with the usual `ChangesStatus` tracking and rollbacks.
+fn apply_history_steps(model: &mut Model, steps: Vec<Step>, orders: &mut impl Orders<Msg>) {
+    let movies = match &mut model.clients {
+        RemoteData::Loaded(movies) => movies,
+        _ => return,
+    };
+    let mut requests = Vec::new();
+    for Step { movie_id, from, to } in steps {
+        model.unsaved_movies.remove(&movie_id);
+        match (from, to) {
+            (Some(from), None) => {
+                movies.remove(&movie_id);
+                model.selected_movies.remove(&movie_id);
+                requests.push((sync::Request::Delete(movie_id), Rollback::RestoreMovie(movie_id, from)));
+            },
+            (None, Some(to)) => {
+                movies.insert(movie_id, to.clone());
+                requests.push((sync::Request::Create(movie_id, to), Rollback::RemoveMovies(vec![movie_id])));
+            },
+            (Some(from), Some(mut to)) => {
+                // The recorded movie may have an outdated version.
+                if let Some(movie) = movies.get(&movie_id) {
+                    to.version = movie.version;
+                }
+                movies.insert(movie_id, to.clone());
+                requests.push((sync::Request::Update(movie_id, to), Rollback::RestoreMovie(movie_id, from)));
+            },
+            (None, None) => {},
+        }
+    }
+    send_requests(model, requests, orders);
+}
+
+/// Ctrl+Z undoes and Ctrl+Shift+Z redoes (Cmd instead of Ctrl on macOS).
+fn on_key_down(event: web_sys::Event) -> Option<Msg> {
+    let event = event.unchecked_into::<web_sys::KeyboardEvent>();
+    if not(event.ctrl_key() || event.meta_key()) || event.key().to_lowercase() != "z" {
+        return None;
+    }
+    // Text fields have their own undo.
+    let in_text_field = event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .map_or(false, |element| matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA"));
+    if in_text_field {
+        return None;
+    }
+    event.prevent_default();
+    Some(if event.shift_key() { Msg::Redo } else { Msg::Undo })
+}
+
+/// Filters and sorts the movies again; call it when the movies, filters or sort change.
+fn refresh_visible_movies(model: &mut Model) {
+    match &model.clients {
+        RemoteData::Loaded(movies) => {
+            let filters = &model.filters;
+            model.visible_movie_ids = model.sort.sorted_ids(movies.iter().filter(|(_, movie)| filters.matches(movie)));
+            model.genres = movies.values().flat_map(|movie| movie.genres.iter().cloned()).collect();
+        },
+        _ => {
+            model.visible_movie_ids.clear();
+            model.genres.clear();
+        },
+    }
+}
+
+/// Call after changing the movies; refreshes the list and schedules caching.
+fn movies_changed(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    refresh_visible_movies(model);
+    schedule_caching(model, orders);
+}
+
+fn schedule_caching(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    // Replacing the handle aborts the previous timeout.
+    model.cache_movies_handle = Some(orders.perform_cmd_with_handle(
+        cmds::timeout(CACHE_MOVIES_DEBOUNCE_MS, || Msg::CacheMovies)
+    ));
+}
+
+/// The movies the user sees in the list, in the list order.
+fn visible_movies<'a>(movies: &'a BTreeMap<MovieId, Movie>, visible_movie_ids: &[MovieId]) -> Vec<(&'a MovieId, &'a Movie)> {
+    visible_movie_ids
+        .iter()
+        .filter_map(|movie_id| movies.get_key_value(movie_id))
+        .collect()
+}
+
+fn download(file_name: &str, mime_type: &str, content: &str) -> Result<(), JsValue> {
+    let mut blob_options = web_sys::BlobPropertyBag::new();
+    blob_options.type_(mime_type);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(
+        &js_sys::Array::of1(&JsValue::from_str(content)),
+        &blob_options,
+    )?;
+    let object_url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let link = document().create_element("a")?.unchecked_into::<web_sys::HtmlAnchorElement>();
+    link.set_href(&object_url);
+    link.set_download(file_name);
+    link.click();
+
+    web_sys::Url::revoke_object_url(&object_url)
+}
+
+/// Keeps the filters and sort in the browser URL so the list view is bookmarkable.
+/// `go_and_replace` doesn't trigger `UrlChanged`, so the page isn't re-initialized.
+/// The list is scrolled back to the top, because its rows have changed.
+fn url_search_changed(model: &mut Model) {
+    refresh_visible_movies(model);
+    // The old offset could point past the end of a narrowed list and render an empty window.
+    model.virtual_list.scroll_top = 0.;
+    if let Some(viewport) = model.viewport.get() {
+        viewport.set_scroll_top(0);
+    }
+    model.filters.write_url_search(model.url.search_mut());
+    model.sort.write_url_search(model.url.search_mut());
+    model.url.go_and_replace();
+}
+
+fn save_movie_edits(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    model.save_edits_handle = None;
+    let movies = match &model.clients {
+        RemoteData::Loaded(movies) => movies,
+        _ => return,
+    };
+    let requests = mem::take(&mut model.unsaved_movies)
+        .into_iter()
+        .filter_map(|(movie_id, saved_movie)| {
+            let movie = movies.get(&movie_id)?.clone();
+            Some((sync::Request::Update(movie_id, movie), Rollback::RestoreMovie(movie_id, saved_movie)))
+        })
+        .collect();
+    send_requests(model, requests, orders);
+}
+
+/// Refreshes the list and the cache with the changed movies and sends the requests, one command per request.
+/// Requests are queued while offline or while older requests wait in the queue.
+fn send_requests(model: &mut Model, requests: Vec<(sync::Request, Rollback)>, orders: &mut impl Orders<Msg>) {
+    if requests.is_empty() {
+        return;
+    }
+    movies_changed(model, orders);
+    for (request, rollback) in requests {
+        if model.sync_queue.should_queue() {
+            model.sync_queue.push(request);
+            continue;
+        }
+        // The update would be refused, because the request in flight changes the movie version.
+        if let sync::Request::Update(movie_id, movie) = &request {
+            if model.saving_movies.contains(movie_id) {
+                let saved_movie = rollback.restored_movie(*movie_id, movie);
+                model.unsaved_movies.entry(*movie_id).or_insert(saved_movie);
+                continue;
+            }
+        }
+        if let Some(movie_id) = request.versioned_movie_id() {
+            model.saving_movies.insert(movie_id);
+        }
+        model.changes_status = request_started(&model.changes_status);
+        let sent_request = request.clone();
+        orders.perform_cmd(async move {
+            Msg::ChangesSaved(request, sent_request.send().await, rollback)
+        });
+    }
+}
+
+/// Sends the first queued request; the next one is sent when it succeeds.
+fn replay_sync_queue(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if model.sync_queue.offline || model.sync_queue.replaying {
+        return;
+    }
+    if let Some(request) = model.sync_queue.front().cloned() {
+        model.sync_queue.replaying = true;
+        model.changes_status = request_started(&model.changes_status);
+        orders.perform_cmd(async move { Msg::QueuedRequestSent(request.send().await) });
+    }
+}
+
+/// Replays the queue after a delay growing with every failed attempt,
+/// because the server may be unreachable while the browser is online.
+fn schedule_replay(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if model.replay_handle.is_some() {
+        return;
+    }
+    let delay = model.sync_queue.next_retry_delay();
+    model.replay_handle = Some(orders.perform_cmd_with_handle(cmds::timeout(delay, || Msg::ReplaySyncQueue)));
+}
+
+/// Stores the new version, so the next update of the movie isn't refused.
+fn set_movie_version(model: &mut Model, new_version: sync::NewVersion, orders: &mut impl Orders<Msg>) {
+    let (movie_id, version) = match new_version {
+        Some(new_version) => new_version,
+        None => return,
+    };
+    let movie = match &mut model.clients {
+        RemoteData::Loaded(movies) => movies.get_mut(&movie_id),
+        _ => None,
+    };
+    if let Some(movie) = movie {
+        movie.version = version;
+        schedule_caching(model, orders);
+    }
+    if let Some(saved_movie) = model.unsaved_movies.get_mut(&movie_id) {
+        saved_movie.version = version;
+    }
+    model.sync_queue.set_version(movie_id, version);
+}
+
+/// The local movie stays until the user compares it with the server one in the conflict dialog.
+fn fetch_server_movie(model: &mut Model, movie_id: MovieId, orders: &mut impl Orders<Msg>) {
+    // The dialog contains all local changes.
+    model.unsaved_movies.remove(&movie_id);
+    orders.perform_cmd(async move { Msg::ServerMovieFetched(movie_id, request_movie(movie_id).await) });
+}
+
+/// Replaces the local movie with the resolved one and saves it, unless it's the server movie.
+fn resolve_conflict(model: &mut Model, conflict: &conflict::Model, movie: Movie, orders: &mut impl Orders<Msg>) {
+    let movie_id = conflict.movie_id();
+    let movies = match &mut model.clients {
+        RemoteData::Loaded(movies) => movies,
+        _ => return,
+    };
+    // The movie may have been deleted while the dialog was open.
+    if not(movies.contains_key(&movie_id)) {
+        return;
+    }
+    movies.insert(movie_id, movie.clone());
+    // The recorded changes are based on the refused local movie.
+    model.history.clear();
+    if &movie == conflict.theirs() {
+        movies_changed(model, orders);
+        return;
+    }
+    let rollback = Rollback::RestoreMovie(movie_id, conflict.theirs().clone());
+    send_requests(model, vec![(sync::Request::Update(movie_id, movie), rollback)], orders);
+}
+
+/// How to revert an optimistic change when the server refuses it.
+pub enum Rollback {
+    RestoreMovie(MovieId, Movie),
+    RestoreRating(MovieId, Option<u8>),
+    RemoveMovies(Vec<MovieId>),
+    RestoreMovies(Vec<(MovieId, Movie)>),
+    /// The movies weren't in the list before.
+    RemoveFromList(ListId, Vec<MovieId>),
+}
+
+impl Rollback {
+    /// The movie before the change, given the changed one.
+    fn restored_movie(self, movie_id: MovieId, movie: &Movie) -> Movie {
+        let mut movies = BTreeMap::new();
+        movies.insert(movie_id, movie.clone());
+        self.apply(&mut movies);
+        movies.remove(&movie_id).unwrap_or_else(|| movie.clone())
+    }
+
+    fn apply(self, movies: &mut BTreeMap<MovieId, Movie>) {
+        match self {
+            Self::RestoreMovie(movie_id, movie) => {
+                movies.insert(movie_id, movie);
+            },
+            Self::RestoreRating(movie_id, rating) => {
+                if let Some(movie) = movies.get_mut(&movie_id) {
+                    movie.rating = rating;
+                }
+            },
+            Self::RemoveMovies(movie_ids) => {
+                for movie_id in movie_ids {
+                    movies.remove(&movie_id);
+                }
+            },
+            Self::RestoreMovies(original_movies) => movies.extend(original_movies),
+            // Applied by `apply_to_lists`.
+            Self::RemoveFromList(..) => {},
+        }
+    }
+
+    fn apply_to_lists(&self, lists: &mut BTreeMap<ListId, List>) {
+        if let Self::RemoveFromList(list_id, movie_ids) = self {
+            if let Some(list) = lists.get_mut(list_id) {
+                list.movie_ids.retain(|movie_id| not(movie_ids.contains(movie_id)));
+            }
+        }
+    }
+}
+
+fn request_started(changes_status: &ChangesStatus) -> ChangesStatus {
+    match changes_status {
+        ChangesStatus::Saving { requests_in_flight, failed } => {
+            ChangesStatus::Saving { requests_in_flight: requests_in_flight + 1, failed: *failed }
+        },
+        ChangesStatus::NoChanges | ChangesStatus::Saved(_) | ChangesStatus::NotSaved => {
+            ChangesStatus::Saving { requests_in_flight: 1, failed: false }
+        },
+    }
+}
+
+/// A failure isn't hidden by requests finishing successfully after it.
+fn request_finished(changes_status: &ChangesStatus, succeeded: bool) -> ChangesStatus {
+    let failed = not(succeeded) || matches!(changes_status, ChangesStatus::Saving { failed: true, .. });
+    match changes_status {
+        ChangesStatus::Saving { requests_in_flight, .. } if *requests_in_flight > 1 => {
+            ChangesStatus::Saving { requests_in_flight: requests_in_flight - 1, failed }
+        },
+        _ if failed => ChangesStatus::NotSaved,
+        _ => ChangesStatus::Saved(Local::now()),
     }
 }
 
@@ -81,6 +2082,1247 @@ pub fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
 //     View
 // ------ ------
 
-pub fn view(model: &Model) -> Node<Msg> {
-    div!["Movies view"]
+pub fn view(model: &Model, base_url: &Url) -> Node<Msg> {
+    div![
+        s()
+            .py(rem(1.5))
+            .px(rem(0.75)),
+        view_sync_status(&model.sync_queue),
+        view_changes_status(&model.changes_status),
+        view_errors(&model.errors),
+        IF!(model.undo_toast_handle.is_some() => view_undo_toast(&model.history)),
+        match &model.clients {
+            RemoteData::NotAsked => empty![],
+            RemoteData::Loading => view_message("Loading movies..."),
+            RemoteData::Loaded(movies) => {
+                let filtered_count = model.visible_movie_ids.len();
+                div![
+                    view_new_movie_form(&model.new_movie_form),
+                    model.duplicate_warning.as_ref().map(|warning| view_duplicate_warning(warning, movies, base_url)),
+                    button![
+                        C!["button"],
+                        s()
+                            .cursor(CssCursor::Pointer)
+                            .mb(rem(1)),
+                        "Import CSV",
+                        ev(Ev::Click, |_| Msg::OpenImport),
+                    ],
+                    view_export_menu(model.export_menu_visible, filtered_count),
+                    button![
+                        C!["button"],
+                        s()
+                            .cursor(CssCursor::Pointer)
+                            .mb(rem(1))
+                            .ml(rem(0.5)),
+                        "Find duplicates",
+                        ev(Ev::Click, |_| Msg::FindDuplicates),
+                    ],
+                    button![
+                        C!["button"],
+                        s()
+                            .cursor(CssCursor::Pointer)
+                            .mb(rem(1))
+                            .ml(rem(0.5)),
+                        "Pick something for tonight",
+                        ev(Ev::Click, |_| Msg::OpenPicker),
+                    ],
+                    model.import.as_ref().map(|import| import::view(import).map_msg(Msg::ImportMsg)),
+                    model.import_review.as_ref().map(|review| view_import_review(review, movies, base_url)),
+                    model.duplicate_groups.as_ref().map(|groups| view_duplicates_report(groups, movies, base_url)),
+                    model.merge.as_ref().map(|merge| merge::view(merge).map_msg(Msg::MergeMsg)),
+                    model.picker.as_ref().map(|picker| picker::view(picker, movies, base_url).map_msg(Msg::PickerMsg)),
+                    model.conflicts.front().map(|conflict| conflict::view(conflict).map_msg(Msg::ConflictMsg)),
+                    view_filters(&model.filters, &model.genres),
+                    view_sort(&model.sort),
+                    view_bulk_actions(
+                        &model.selected_movies,
+                        filtered_count,
+                        &model.bulk_tag,
+                        &model.lists,
+                        model.bulk_list,
+                    ),
+                    if movies.is_empty() {
+                        view_message("No movies yet.")
+                    } else if filtered_count == 0 {
+                        view_message("No movies match the filters.")
+                    } else {
+                        view_movies(
+                            &model.visible_movie_ids,
+                            movies,
+                            &model.virtual_list,
+                            &model.viewport,
+                            model.movie_edit.as_ref(),
+                            &model.selected_movies,
+                            &model.loaded_posters,
+                            base_url,
+                        )
+                    }
+                ]
+            },
+        }
+    ]
+}
+
+fn view_export_menu(menu_visible: bool, movie_count: usize) -> Node<Msg> {
+    div![
+        s()
+            .display(CssDisplay::InlineBlock)
+            .ml(rem(0.5))
+            .position(CssPosition::Relative),
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .mb(rem(1)),
+            attrs!{At::Disabled => (movie_count == 0).as_at_value()},
+            format!("Export {} movies ▾", movie_count),
+            ev(Ev::Click, |_| Msg::ToggleExportMenu),
+        ],
+        IF!(menu_visible => div![
+            s()
+                .bg_color("white")
+                .border("1px solid #dbdbdb")
+                .border_radius(px(4))
+                .left("0")
+                .position(CssPosition::Absolute)
+                .top(pc(100))
+                .z_index("20"),
+            ExportFormat::ALL.iter().map(|format| {
+                let format = *format;
+                a![
+                    s()
+                        .cursor(CssCursor::Pointer)
+                        .display(CssDisplay::Block)
+                        .px(rem(1))
+                        .py(rem(0.5))
+                        .white_space(CssWhiteSpace::NoWrap),
+                    s()
+                        .hover()
+                        .bg_color("#f5f5f5"),
+                    format.label(),
+                    ev(Ev::Click, move |_| Msg::Export(format)),
+                ]
+            })
+        ]),
+    ]
+}
+
+fn view_undo_toast(history: &History) -> Node<Msg> {
+    let s_button = || {
+        s()
+            .bg_color("transparent")
+            .border("none")
+            .color("#48c774")
+            .cursor(CssCursor::Pointer)
+            .font_weight("600")
+            .ml(rem(0.75))
+    };
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .bg_color("#363636")
+            .border_radius(px(4))
+            .bottom(rem(1.5))
+            .color("white")
+            .display(CssDisplay::Flex)
+            .left(rem(1.5))
+            .position(CssPosition::Fixed)
+            .px(rem(1))
+            .py(rem(0.75))
+            .z_index("30"),
+        span![history.undo_label().unwrap_or("Nothing to undo")],
+        history.undo_label().map(|_| button![
+            s_button(),
+            attrs!{At::Title => "Ctrl+Z"},
+            "Undo",
+            ev(Ev::Click, |_| Msg::Undo),
+        ]),
+        history.redo_label().map(|label| button![
+            s_button(),
+            attrs!{At::Title => format!("Redo: {} (Ctrl+Shift+Z)", label)},
+            "Redo",
+            ev(Ev::Click, |_| Msg::Redo),
+        ]),
+        button![
+            s_button(),
+            s()
+                .color("#b5b5b5"),
+            attrs!{At::Title => "Close"},
+            "×",
+            ev(Ev::Click, |_| Msg::HideUndoToast),
+        ],
+    ]
+}
+
+fn view_sync_status(sync_queue: &SyncQueue) -> Node<Msg> {
+    if not(sync_queue.offline) && sync_queue.is_empty() {
+        return empty![];
+    }
+    let pending_changes = match sync_queue.len() {
+        0 => String::new(),
+        1 => "1 pending change".to_owned(),
+        count => format!("{} pending changes", count),
+    };
+    p![
+        s()
+            .font_size(rem(0.875))
+            .mb(rem(0.75))
+            .text_align(CssTextAlign::Right),
+        IF!(sync_queue.offline => span![
+            C!["tag"],
+            s()
+                .bg_color("#f14668")
+                .border_radius(px(4))
+                .color("white")
+                .mr(rem(0.5))
+                .px(rem(0.5))
+                .py(rem(0.125)),
+            "Offline",
+        ]),
+        span![
+            s()
+                .color("#7a7a7a"),
+            if sync_queue.offline {
+                pending_changes
+            } else {
+                format!("Syncing {}…", pending_changes)
+            }
+        ],
+    ]
+}
+
+fn view_changes_status(changes_status: &ChangesStatus) -> Node<Msg> {
+    let text = match changes_status {
+        ChangesStatus::NoChanges => return empty![],
+        ChangesStatus::Saving { .. } => "Saving…".to_owned(),
+        ChangesStatus::Saved(date_time) => format!("Saved at {}", date_time.format("%H:%M")),
+        ChangesStatus::NotSaved => "Not all changes saved".to_owned(),
+    };
+    p![
+        s()
+            .color("#7a7a7a")
+            .font_size(rem(0.875))
+            .mb(rem(0.75))
+            .text_align(CssTextAlign::Right),
+        text
+    ]
+}
+
+fn view_errors(errors: &[String]) -> Node<Msg> {
+    if errors.is_empty() {
+        return empty![];
+    }
+    div![
+        C!["notification"],
+        s()
+            .bg_color("#feecf0")
+            .color("#cc0f35")
+            .border_radius(px(4))
+            .mb(rem(1.5))
+            .py(rem(1.25))
+            .px(rem(1.5)),
+        ul![
+            errors.iter().map(|error| li![error])
+        ],
+        button![
+            C!["button"],
+            s()
+                .mt(rem(0.75))
+                .cursor(CssCursor::Pointer),
+            "Clear errors",
+            ev(Ev::Click, |_| Msg::ClearErrors),
+        ]
+    ]
+}
+
+fn view_new_movie_form(form: &NewMovieForm) -> Node<Msg> {
+    let errors = &form.errors;
+    form![
+        s()
+            .border("1px solid #ededed")
+            .border_radius(px(4))
+            .mb(rem(1.5))
+            .p(rem(1)),
+        ev(Ev::Submit, |event| {
+            event.prevent_default();
+            Msg::AddMovie
+        }),
+        view_form_field("Title", &form.title, errors.title.as_ref(), Msg::NewMovieTitleChanged),
+        view_form_field("Year", &form.year, errors.year.as_ref(), Msg::NewMovieYearChanged),
+        view_form_field("Genres (comma separated)", &form.genres, None, Msg::NewMovieGenresChanged),
+        view_form_field("Runtime (minutes)", &form.runtime, errors.runtime.as_ref(), Msg::NewMovieRuntimeChanged),
+        view_form_field("Rating (0.5-5 stars)", &form.rating, errors.rating.as_ref(), Msg::NewMovieRatingChanged),
+        view_form_field("Poster URL", &form.poster_url, None, Msg::NewMoviePosterUrlChanged),
+        view_form_field("Synopsis", &form.synopsis, None, Msg::NewMovieSynopsisChanged),
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .mt(rem(0.5)),
+            attrs!{At::Type => "submit"},
+            "Add movie",
+        ]
+    ]
+}
+
+fn view_duplicate_warning(
+    warning: &DuplicateWarning,
+    movies: &BTreeMap<MovieId, Movie>,
+    base_url: &Url,
+) -> Node<Msg> {
+    div![
+        C!["notification"],
+        s()
+            .bg_color("#fffbeb")
+            .border_radius(px(4))
+            .color("#947600")
+            .mb(rem(1.5))
+            .py(rem(1.25))
+            .px(rem(1.5)),
+        p![format!("\"{}\" may already be in your collection:", warning.movie.title)],
+        ul![
+            s()
+                .my(rem(0.5)),
+            warning.existing_movie_ids.iter().filter_map(|movie_id| {
+                movies.get(movie_id).map(|movie| view_duplicate_link(*movie_id, movie, base_url, "Open existing"))
+            })
+        ],
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .mr(rem(0.5)),
+            "Add anyway",
+            ev(Ev::Click, |_| Msg::AddMovieAnyway),
+        ],
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer),
+            "Cancel",
+            ev(Ev::Click, |_| Msg::DismissDuplicateWarning),
+        ],
+    ]
+}
+
+fn view_import_review(review: &ImportReview, movies: &BTreeMap<MovieId, Movie>, base_url: &Url) -> Node<Msg> {
+    div![
+        s()
+            .border("1px solid #ededed")
+            .border_radius(px(4))
+            .mb(rem(1.5))
+            .p(rem(1)),
+        h2![
+            s()
+                .font_size(rem(1.25))
+                .font_weight("600")
+                .mb(rem(0.5)),
+            "Confirm import",
+        ],
+        p![
+            s()
+                .mb(rem(0.5)),
+            format!(
+                "{} imported movies may already be in your collection. Checked movies are merged into the existing ones.",
+                review.duplicates_of.iter().flatten().count(),
+            ),
+        ],
+        ul![
+            s()
+                .mb(rem(0.5)),
+            review
+                .movies
+                .iter()
+                .zip(&review.duplicates_of)
+                .enumerate()
+                .filter_map(|(index, (imported_movie, movie_id))| {
+                    let movie_id = (*movie_id)?;
+                    let movie = movies.get(&movie_id)?;
+                    Some(li![
+                        label![
+                            input![
+                                attrs!{
+                                    At::Type => "checkbox",
+                                    At::Checked => not(review.kept_separate.contains(&index)).as_at_value(),
+                                },
+                                ev(Ev::Change, move |_| Msg::ToggleImportMerge(index)),
+                            ],
+                            format!(" \"{}\"", imported_movie.title),
+                            imported_movie.year.map(|year| format!(" ({})", year)),
+                            " => ",
+                        ],
+                        a![
+                            attrs!{At::Href => crate::Urls::new(base_url).movie(movie_id)},
+                            &movie.title,
+                        ],
+                        movie.year.map(|year| format!(" ({})", year)),
+                    ])
+                })
+        ],
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .mr(rem(0.5)),
+            format!("Import {} movies", review.movies.len()),
+            ev(Ev::Click, |_| Msg::ConfirmImport),
+        ],
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer),
+            "Cancel",
+            ev(Ev::Click, |_| Msg::CancelImport),
+        ],
+    ]
+}
+
+fn view_duplicates_report(
+    groups: &[Vec<MovieId>],
+    movies: &BTreeMap<MovieId, Movie>,
+    base_url: &Url,
+) -> Node<Msg> {
+    div![
+        s()
+            .border("1px solid #ededed")
+            .border_radius(px(4))
+            .mb(rem(1.5))
+            .p(rem(1)),
+        h2![
+            s()
+                .font_size(rem(1.25))
+                .font_weight("600")
+                .mb(rem(0.5)),
+            "Likely duplicates",
+        ],
+        if groups.is_empty() {
+            p!["No duplicates found."]
+        } else {
+            ol![
+                groups.iter().map(|group| li![
+                    s()
+                        .mb(rem(0.75)),
+                    ul![
+                        group.iter().filter_map(|movie_id| {
+                            movies.get(movie_id).map(|movie| view_duplicate_link(*movie_id, movie, base_url, "Open"))
+                        })
+                    ],
+                    // Larger groups are merged one pair at a time.
+                    group.iter().skip(1).map(|movie_id| {
+                        let (first_id, movie_id) = (group[0], *movie_id);
+                        let title = movies.get(&movie_id).map(|movie| movie.title.as_str()).unwrap_or_default();
+                        button![
+                            C!["button"],
+                            s()
+                                .cursor(CssCursor::Pointer)
+                                .font_size(rem(0.875))
+                                .mr(rem(0.5))
+                                .mt(rem(0.25)),
+                            if group.len() > 2 { format!("Merge \"{}\"…", title) } else { "Merge…".to_owned() },
+                            ev(Ev::Click, move |_| Msg::StartMerge(first_id, movie_id)),
+                        ]
+                    })
+                ])
+            ]
+        },
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .mt(rem(0.5)),
+            "Close",
+            ev(Ev::Click, |_| Msg::CloseDuplicatesReport),
+        ],
+    ]
+}
+
+fn view_duplicate_link(movie_id: MovieId, movie: &Movie, base_url: &Url, link_text: &str) -> Node<Msg> {
+    li![
+        strong![&movie.title],
+        movie.year.map(|year| format!(" ({})", year)),
+        span![
+            s()
+                .color("#7a7a7a")
+                .mx(rem(0.5)),
+            format!("added {}", added_at(movie_id).format("%Y-%m-%d")),
+        ],
+        a![
+            attrs!{At::Href => crate::Urls::new(base_url).movie(movie_id)},
+            link_text,
+        ],
+    ]
+}
+
+fn view_form_field(
+    label: &str,
+    value: &str,
+    error: Option<&String>,
+    on_input: impl FnOnce(String) -> Msg + Clone + 'static,
+) -> Node<Msg> {
+    div![
+        s()
+            .mb(rem(0.5)),
+        label![
+            s()
+                .display(CssDisplay::Block)
+                .font_weight("600"),
+            label,
+            input![
+                s()
+                    .border("1px solid #dbdbdb")
+                    .border_radius(px(4))
+                    .display(CssDisplay::Block)
+                    .font_weight("400")
+                    .px(rem(0.5))
+                    .py(rem(0.25))
+                    .w(pc(100)),
+                if error.is_some() {
+                    s().border_color("#f14668")
+                } else {
+                    s()
+                },
+                attrs!{At::Value => value},
+                input_ev(Ev::Input, on_input),
+            ],
+        ],
+        error.map(|error| p![
+            s()
+                .color("#f14668")
+                .font_size(rem(0.75)),
+            error
+        ])
+    ]
+}
+
+fn view_filters(filters: &Filters, genres: &BTreeSet<String>) -> Node<Msg> {
+    let s_control = || {
+        s()
+            .border("1px solid #dbdbdb")
+            .border_radius(px(4))
+            .mr(rem(0.5))
+            .px(rem(0.5))
+            .py(rem(0.25))
+    };
+
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .flex_wrap(CssFlexWrap::Wrap)
+            .mb(rem(1)),
+        input![
+            s_control(),
+            s()
+                .flex_grow("1"),
+            attrs!{
+                At::Type => "search",
+                At::Placeholder => "Search",
+                At::Value => filters.search,
+            },
+            input_ev(Ev::Input, Msg::SearchChanged),
+        ],
+        select![
+            s_control(),
+            option![
+                attrs!{At::Value => ""},
+                "All genres",
+            ],
+            genres.iter().map(|genre| option![
+                attrs!{
+                    At::Value => genre,
+                    At::Selected => (filters.genre.as_ref() == Some(genre)).as_at_value(),
+                },
+                genre
+            ]),
+            input_ev(Ev::Change, Msg::GenreFilterChanged),
+        ],
+        input![
+            s_control(),
+            s()
+                .w(rem(6)),
+            attrs!{
+                At::Type => "number",
+                At::Placeholder => "Year from",
+                At::Value => filters.year_from.map(|year| year.to_string()).unwrap_or_default(),
+            },
+            input_ev(Ev::Input, Msg::YearFromFilterChanged),
+        ],
+        input![
+            s_control(),
+            s()
+                .w(rem(6)),
+            attrs!{
+                At::Type => "number",
+                At::Placeholder => "Year to",
+                At::Value => filters.year_to.map(|year| year.to_string()).unwrap_or_default(),
+            },
+            input_ev(Ev::Input, Msg::YearToFilterChanged),
+        ],
+        select![
+            s_control(),
+            option![
+                attrs!{At::Value => ""},
+                "Any status",
+            ],
+            WatchStatus::ALL.iter().map(|watch_status| option![
+                attrs!{
+                    At::Value => watch_status.as_str(),
+                    At::Selected => (filters.watch_status == Some(*watch_status)).as_at_value(),
+                },
+                watch_status.label()
+            ]),
+            input_ev(Ev::Change, Msg::WatchStatusFilterChanged),
+        ],
+        IF!(filters.is_active() => button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer),
+            "Clear filters",
+            ev(Ev::Click, |_| Msg::ClearFilters),
+        ]),
+    ]
+}
+
+fn view_sort(sort: &Sort) -> Node<Msg> {
+    let view_key_select = |selected: Option<SortKey>, none_label: Option<&str>, on_change: fn(String) -> Msg| {
+        select![
+            s()
+                .border("1px solid #dbdbdb")
+                .border_radius(px(4))
+                .mx(rem(0.25))
+                .px(rem(0.5))
+                .py(rem(0.25)),
+            none_label.map(|none_label| option![
+                attrs!{At::Value => ""},
+                none_label,
+            ]),
+            SortKey::ALL.iter().map(|key| option![
+                attrs!{
+                    At::Value => key.as_str(),
+                    At::Selected => (selected == Some(*key)).as_at_value(),
+                },
+                key.label(),
+            ]),
+            input_ev(Ev::Change, on_change),
+        ]
+    };
+    let view_order_button = |order: SortOrder, msg: fn() -> Msg| {
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .mr(rem(0.5)),
+            attrs!{At::Title => "Toggle order"},
+            match order {
+                SortOrder::Ascending => "↑",
+                SortOrder::Descending => "↓",
+            },
+            ev(Ev::Click, move |_| msg()),
+        ]
+    };
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .flex_wrap(CssFlexWrap::Wrap)
+            .font_size(rem(0.875))
+            .mb(rem(1)),
+        "Sort by",
+        view_key_select(Some(sort.primary.key), None, Msg::SortKeyChanged),
+        view_order_button(sort.primary.order, || Msg::SortOrderToggled),
+        "then by",
+        view_key_select(sort.secondary.map(|secondary| secondary.key), Some("—"), Msg::SecondarySortKeyChanged),
+        sort.secondary.map(|secondary| view_order_button(secondary.order, || Msg::SecondarySortOrderToggled)),
+    ]
+}
+
+fn view_bulk_actions(
+    selected_movies: &BTreeSet<MovieId>,
+    filtered_count: usize,
+    bulk_tag: &str,
+    lists: &BTreeMap<ListId, List>,
+    bulk_list: Option<ListId>,
+) -> Node<Msg> {
+    let s_button = || {
+        s()
+            .cursor(CssCursor::Pointer)
+            .font_size(rem(0.875))
+            .mr(rem(0.5))
+    };
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .flex_wrap(CssFlexWrap::Wrap)
+            .mb(rem(0.75)),
+        button![
+            C!["button"],
+            s_button(),
+            attrs!{At::Disabled => (filtered_count == 0).as_at_value()},
+            format!("Select all {}", filtered_count),
+            ev(Ev::Click, |_| Msg::SelectAllFiltered),
+        ],
+        IF!(not(selected_movies.is_empty()) => vec![
+            span![
+                s()
+                    .font_weight("600")
+                    .mr(rem(0.75)),
+                format!("{} selected", selected_movies.len()),
+            ],
+            form![
+                s()
+                    .display(CssDisplay::Flex)
+                    .mr(rem(0.5)),
+                ev(Ev::Submit, |event| {
+                    event.prevent_default();
+                    Msg::BulkAddTag
+                }),
+                input![
+                    s()
+                        .border("1px solid #dbdbdb")
+                        .border_radius(px(4))
+                        .mr(rem(0.25))
+                        .px(rem(0.5)),
+                    attrs!{
+                        At::Value => bulk_tag,
+                        At::Placeholder => "Tag",
+                    },
+                    input_ev(Ev::Input, Msg::BulkTagChanged),
+                ],
+                button![
+                    C!["button"],
+                    s_button(),
+                    attrs!{At::Type => "submit"},
+                    "Add tag",
+                ],
+            ],
+            IF!(not(lists.is_empty()) => form![
+                s()
+                    .display(CssDisplay::Flex)
+                    .mr(rem(0.5)),
+                ev(Ev::Submit, |event| {
+                    event.prevent_default();
+                    Msg::BulkAddToList
+                }),
+                select![
+                    s()
+                        .mr(rem(0.25)),
+                    option![
+                        attrs!{
+                            At::Value => "",
+                            At::Selected => bulk_list.is_none().as_at_value(),
+                        },
+                        "— list —",
+                    ],
+                    lists::sorted_lists(lists).into_iter().map(|(list_id, list)| option![
+                        attrs!{
+                            At::Value => list_id,
+                            At::Selected => (bulk_list == Some(*list_id)).as_at_value(),
+                        },
+                        &list.name,
+                    ]),
+                    input_ev(Ev::Change, Msg::BulkListChanged),
+                ],
+                button![
+                    C!["button"],
+                    s_button(),
+                    attrs!{
+                        At::Type => "submit",
+                        At::Disabled => bulk_list.is_none().as_at_value(),
+                    },
+                    "Add to list",
+                ],
+            ]),
+            button![
+                C!["button"],
+                s_button(),
+                "Mark watched",
+                ev(Ev::Click, |_| Msg::BulkMarkWatched),
+            ],
+            button![
+                C!["button"],
+                s_button(),
+                s()
+                    .color("#f14668"),
+                "Delete",
+                ev(Ev::Click, |_| Msg::BulkDelete),
+            ],
+            button![
+                C!["button"],
+                s_button(),
+                "Clear selection",
+                ev(Ev::Click, |_| Msg::ClearSelection),
+            ],
+        ]),
+    ]
+}
+
+fn view_message(message: &str) -> Node<Msg> {
+    p![
+        s()
+            .color("#7a7a7a")
+            .text_align(CssTextAlign::Center)
+            .py(rem(3)),
+        message
+    ]
+}
+
+fn view_movies(
+    movie_ids: &[MovieId],
+    movies: &BTreeMap<MovieId, Movie>,
+    virtual_list: &VirtualList,
+    viewport: &ElRef<web_sys::Element>,
+    movie_edit: Option<&MovieEdit>,
+    selected_movies: &BTreeSet<MovieId>,
+    loaded_posters: &BTreeSet<String>,
+    base_url: &Url,
+) -> Node<Msg> {
+    let visible_range = virtual_list.visible_range(movie_ids.len());
+    div![
+        el_ref(viewport),
+        s()
+            .h("70vh")
+            .overflow_y("auto")
+            .position(CssPosition::Relative),
+        ev(Ev::Scroll, |event| {
+            event
+                .target()
+                .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                .map(|viewport| Msg::MoviesScrolled(f64::from(viewport.scroll_top())))
+        }),
+        div![
+            s()
+                .h(px(virtual_list.total_height(movie_ids.len())))
+                .position(CssPosition::Relative),
+            ul![
+                s()
+                    .left("0")
+                    .position(CssPosition::Absolute)
+                    .right("0")
+                    .top(px(virtual_list.offset_top(visible_range.start))),
+                movie_ids[visible_range].iter().filter_map(|movie_id| {
+                    let movie = movies.get(movie_id)?;
+                    let movie_edit = movie_edit.filter(|edit| edit.movie_id == *movie_id);
+                    let selected = selected_movies.contains(movie_id);
+                    Some(view_movie(*movie_id, movie, movie_edit, selected, loaded_posters, base_url))
+                })
+            ]
+        ]
+    ]
+}
+
+fn view_movie(
+    movie_id: MovieId,
+    movie: &Movie,
+    movie_edit: Option<&MovieEdit>,
+    selected: bool,
+    loaded_posters: &BTreeSet<String>,
+    base_url: &Url,
+) -> Node<Msg> {
+    let editable = |field, content| view_editable(movie_id, field, movie_edit, content);
+    li![
+        el_key(&movie_id),
+        s()
+            .border_bottom("1px solid #ededed")
+            .display(CssDisplay::Flex)
+            .h(px(ROW_HEIGHT))
+            .overflow("hidden")
+            .py(rem(0.75)),
+        if selected {
+            s().bg_color("#f0f8ff")
+        } else {
+            s()
+        },
+        input![
+            s()
+                .align_self(CssAlignSelf::FlexStart)
+                .cursor(CssCursor::Pointer)
+                .mr(rem(0.75))
+                .mt(rem(0.25)),
+            attrs!{
+                At::Type => "checkbox",
+                At::Checked => selected.as_at_value(),
+                At::Title => "Select (shift-click selects a range)",
+            },
+            mouse_ev(Ev::Click, move |event| Msg::MovieSelectionToggled(movie_id, event.shift_key())),
+        ],
+        view_poster(movie.thumbnail_url.as_ref().or_else(|| movie.poster_url.as_ref()), loaded_posters),
+        div![
+            s()
+                .flex_grow("1"),
+            h3![
+                s()
+                    .font_size(rem(1.25))
+                    .font_weight("600"),
+                editable(MovieField::Title, span![&movie.title]),
+                span![
+                    s()
+                        .color("#7a7a7a")
+                        .font_weight("400")
+                        .ml(rem(0.5)),
+                    editable(MovieField::Year, match movie.year {
+                        Some(year) => span![format!("({})", year)],
+                        None => view_placeholder(MovieField::Year),
+                    }),
+                ],
+            ],
+            view_movie_facts(movie_id, movie, editable),
+            div![
+                s()
+                    .mt(rem(0.5)),
+                editable(MovieField::Synopsis, match &movie.synopsis {
+                    Some(synopsis) => p![
+                        s()
+                            .overflow("hidden")
+                            .raw("display: -webkit-box; -webkit-line-clamp: 2; -webkit-box-orient: vertical;"),
+                        synopsis
+                    ],
+                    None => view_placeholder(MovieField::Synopsis),
+                }),
+            ],
+            p![
+                s()
+                    .color("#7a7a7a")
+                    .font_size(rem(0.75))
+                    .mt(rem(0.5)),
+                format!("Added {}", added_at(movie_id).format("%Y-%m-%d %H:%M")),
+                view_watch_status(movie_id, movie),
+                a![
+                    s()
+                        .ml(rem(0.75)),
+                    attrs!{At::Href => crate::Urls::new(base_url).movie(movie_id)},
+                    "Details",
+                ],
+            ],
+        ],
+        button![
+            C!["delete"],
+            s()
+                .align_self(CssAlignSelf::FlexStart)
+                .bg_color("transparent")
+                .border("none")
+                .color("#7a7a7a")
+                .cursor(CssCursor::Pointer)
+                .font_size(rem(1.25)),
+            s()
+                .hover()
+                .color("#f14668"),
+            attrs!{At::Title => "Delete"},
+            "×",
+            ev(Ev::Click, move |_| Msg::DeleteMovie(movie_id)),
+        ]
+    ]
+}
+
+/// Renders `content` or an input when the user is editing the given `field`.
+fn view_editable(
+    movie_id: MovieId,
+    field: MovieField,
+    movie_edit: Option<&MovieEdit>,
+    content: Node<Msg>,
+) -> Node<Msg> {
+    match movie_edit.filter(|edit| edit.field == field) {
+        Some(edit) => span![
+            input![
+                el_ref(&edit.input),
+                s()
+                    .border("1px solid #dbdbdb")
+                    .border_radius(px(4))
+                    .font_size(rem(1))
+                    .font_weight("400")
+                    .px(rem(0.5))
+                    .py(rem(0.125)),
+                if edit.error.is_some() {
+                    s().border_color("#f14668")
+                } else {
+                    s()
+                },
+                attrs!{
+                    At::Value => &edit.text,
+                    At::Placeholder => field.label(),
+                },
+                input_ev(Ev::Input, Msg::MovieEditChanged),
+                ev(Ev::Blur, |_| Msg::StopMovieEdit),
+                keyboard_ev(Ev::KeyDown, |event| match event.key().as_str() {
+                    "Enter" => Some(Msg::StopMovieEdit),
+                    "Escape" => Some(Msg::CancelMovieEdit),
+                    _ => None,
+                }),
+            ],
+            edit.error.as_ref().map(|error| span![
+                s()
+                    .color("#f14668")
+                    .font_size(rem(0.75))
+                    .font_weight("400")
+                    .ml(rem(0.5)),
+                error
+            ])
+        ],
+        None => span![
+            s()
+                .cursor(CssCursor::Text),
+            s()
+                .hover()
+                .bg_color("#fafafa"),
+            attrs!{At::Title => format!("Edit {}", field.label().to_lowercase())},
+            content,
+            ev(Ev::Click, move |_| Msg::StartMovieEdit(movie_id, field)),
+        ],
+    }
+}
+
+fn view_placeholder(field: MovieField) -> Node<Msg> {
+    span![
+        s()
+            .color("#b5b5b5")
+            .font_weight("400"),
+        format!("+ {}", field.label())
+    ]
+}
+
+fn view_watch_status(movie_id: MovieId, movie: &Movie) -> Node<Msg> {
+    let current_status = movie.watch_status;
+    span![
+        s()
+            .ml(rem(0.75)),
+        strong![current_status.label()],
+        movie.last_watched_on().map(|last_watched_on| {
+            format!(" ({}×, last {})", movie.watch_history.len(), last_watched_on.format("%Y-%m-%d"))
+        }),
+        current_status.transitions().iter().map(|watch_status| {
+            let watch_status = *watch_status;
+            button![
+                s()
+                    .bg_color("transparent")
+                    .border("1px solid #dbdbdb")
+                    .border_radius(px(4))
+                    .cursor(CssCursor::Pointer)
+                    .font_size(rem(0.75))
+                    .ml(rem(0.25))
+                    .px(rem(0.25)),
+                match (current_status, watch_status) {
+                    (WatchStatus::Watched, WatchStatus::Watched) => "Watched again",
+                    (_, WatchStatus::Watched) => "Mark watched",
+                    (_, WatchStatus::Watching) => "Start watching",
+                    (_, WatchStatus::Abandoned) => "Abandon",
+                    (_, WatchStatus::WantToWatch) => "Want to watch",
+                },
+                ev(Ev::Click, move |_| Msg::ChangeWatchStatus(movie_id, watch_status)),
+            ]
+        })
+    ]
+}
+
+/// The placeholder is visible until the lazily loaded poster arrives.
+fn view_poster(poster_url: Option<&String>, loaded_posters: &BTreeSet<String>) -> Node<Msg> {
+    let loaded = poster_url.map_or(false, |poster_url| loaded_posters.contains(poster_url));
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .bg_color("#f5f5f5")
+            .display(CssDisplay::Flex)
+            .flex_shrink("0")
+            .h(px(96))
+            .justify_content(CssJustifyContent::Center)
+            .mr(rem(1))
+            .position(CssPosition::Relative)
+            .w(px(64)),
+        IF!(not(loaded) => span![
+            s()
+                .color("#b5b5b5")
+                .font_size(rem(1.5)),
+            attrs!{At::AriaHidden => "true"},
+            "🎬",
+        ]),
+        poster_url.map(|poster_url| {
+            let loaded_url = poster_url.clone();
+            img![
+                s()
+                    .h(px(96))
+                    .left("0")
+                    .position(CssPosition::Absolute)
+                    .top("0")
+                    .transition_duration("200ms")
+                    .transition_property("opacity")
+                    .w(px(64))
+                    .raw("object-fit: cover;"),
+                if loaded {
+                    s().opacity("1")
+                } else {
+                    s().opacity("0")
+                },
+                attrs!{
+                    At::Src => poster_url,
+                    At::Alt => "Poster",
+                    At::from("loading") => "lazy",
+                },
+                ev(Ev::Load, move |_| Msg::PosterLoaded(loaded_url)),
+            ]
+        })
+    ]
+}
+
+fn view_movie_facts(
+    movie_id: MovieId,
+    movie: &Movie,
+    editable: impl Fn(MovieField, Node<Msg>) -> Node<Msg>,
+) -> Node<Msg> {
+    let separator = || span![" · "];
+    p![
+        s()
+            .color("#4a4a4a")
+            .font_size(rem(0.875)),
+        editable(MovieField::Genres, if movie.genres.is_empty() {
+            view_placeholder(MovieField::Genres)
+        } else {
+            span![movie.genres.join(", ")]
+        }),
+        separator(),
+        editable(MovieField::Runtime, match movie.runtime {
+            Some(runtime) => span![format_runtime(runtime)],
+            None => view_placeholder(MovieField::Runtime),
+        }),
+        separator(),
+        view_rating_stars(movie_id, movie.rating),
+        movie.average_rating.map(|average_rating| span![
+            s()
+                .color("#7a7a7a")
+                .ml(rem(0.25)),
+            format!("(avg {:.1})", average_rating / 2.)
+        ]),
+        separator(),
+        editable(MovieField::Review, match &movie.review {
+            Some(_) => span!["✎ Review"],
+            None => view_placeholder(MovieField::Review),
+        }),
+        movie.review.as_ref().map(|review| button![
+            s()
+                .bg_color("transparent")
+                .border("none")
+                .color(if review.spoiler { "#f14668" } else { "#b5b5b5" })
+                .cursor(CssCursor::Pointer)
+                .font_size(rem(0.75)),
+            attrs!{At::Title => "Toggle spoiler flag"},
+            if review.spoiler { "spoilers" } else { "no spoilers" },
+            ev(Ev::Click, move |_| Msg::ToggleReviewSpoiler(movie_id)),
+        ]),
+        movie.tags.iter().map(|tag| span![
+            s()
+                .color("#3273dc")
+                .ml(rem(0.5)),
+            format!("#{}", tag)
+        ]),
+    ]
+}
+
+/// Each star has two click areas - the left one sets a half star.
+fn view_rating_stars(movie_id: MovieId, rating: Option<u8>) -> Node<Msg> {
+    let rating = rating.unwrap_or_default();
+    span![
+        s()
+            .color("#ffb70f")
+            .white_space(CssWhiteSpace::NoWrap),
+        attrs!{At::Title => if rating == 0 { "Rate".to_owned() } else { format!("{} stars", format_stars(rating)) }},
+        (1..=rating::MAX_HALF_STARS / 2).map(|star| {
+            let full = star * 2;
+            let half = full - 1;
+            let s_click_area = || {
+                s()
+                    .cursor(CssCursor::Pointer)
+                    .h(pc(100))
+                    .position(CssPosition::Absolute)
+                    .top("0")
+                    .w(pc(50))
+            };
+            span![
+                s()
+                    .display(CssDisplay::InlineBlock)
+                    .position(CssPosition::Relative),
+                if rating >= full { "★" } else { "☆" },
+                IF!(rating == half => span![
+                    s()
+                        .left("0")
+                        .overflow("hidden")
+                        .position(CssPosition::Absolute)
+                        .top("0")
+                        .w(pc(50)),
+                    "★"
+                ]),
+                span![
+                    s_click_area()
+                        .left("0"),
+                    ev(Ev::Click, move |_| Msg::RateMovie(movie_id, half)),
+                ],
+                span![
+                    s_click_area()
+                        .left(pc(50)),
+                    ev(Ev::Click, move |_| Msg::RateMovie(movie_id, full)),
+                ],
+            ]
+        })
+    ]
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// Needs the mock API (`cargo make mock_api`) on the configured `API_URL`; run with `cargo make test_mock_api`.
+    #[wasm_bindgen_test]
+    #[ignore]
+    async fn request_movies_from_mock_api() {
+        let movies = request_movies().await.expect("movies from the mock API");
+        let titles = movies.values().map(|movie| movie.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Seven Samurai", "Spirited Away"]);
+        let seven_samurai = movies.values().next().unwrap();
+        assert_eq!(seven_samurai.runtime, Some(207));
+        assert!(seven_samurai.watch_status == WatchStatus::Watched);
+    }
+
+    /// Scrolling renders only the rows in the viewport, looked up by the cached sorted ids,
+    /// so a frame fits into the 16 ms of 60 fps regardless of the collection size.
+    #[wasm_bindgen_test]
+    fn render_rows_of_10k_movies_within_a_frame() {
+        const MOVIE_COUNT: u128 = 10_000;
+        const FRAMES: u32 = 100;
+        const FRAME_BUDGET_MS: f64 = 16.;
+
+        let movies = (0..MOVIE_COUNT)
+            .map(|index| {
+                let movie = Movie {
+                    title: format!("Movie {}", MOVIE_COUNT - index),
+                    year: Some(1900 + (index % 120) as i32),
+                    genres: vec!["Drama".to_owned()],
+                    ..Movie::default()
+                };
+                (MovieId::from(index + 1), movie)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let sort = Sort { primary: SortBy { key: SortKey::Title, order: SortOrder::Ascending }, secondary: None };
+        let movie_ids = sort.sorted_ids(movies.iter());
+        let mut virtual_list = VirtualList::new(ROW_HEIGHT, OVERSCAN_ROWS, DEFAULT_VIEWPORT_HEIGHT);
+        let (viewport, selected_movies, loaded_posters, base_url) =
+            (ElRef::default(), BTreeSet::new(), BTreeSet::new(), Url::new());
+
+        let started_at = js_sys::Date::now();
+        for frame in 0..FRAMES {
+            // Jumps through the whole list.
+            virtual_list.scroll_top = ROW_HEIGHT * f64::from(frame * 97);
+            view_movies(
+                &movie_ids,
+                &movies,
+                &virtual_list,
+                &viewport,
+                None,
+                &selected_movies,
+                &loaded_posters,
+                &base_url,
+            );
+        }
+        let ms_per_frame = (js_sys::Date::now() - started_at) / f64::from(FRAMES);
+        assert!(ms_per_frame < FRAME_BUDGET_MS, "{:.1} ms per frame", ms_per_frame);
+    }
 }
@@ -0,0 +1,266 @@
+use seed::{prelude::*, *};
+
+use seed_style::{pc, px, rem};
+use seed_style::*;
+
+use std::collections::BTreeMap;
+
+use super::{Movie, MovieField, MovieId};
+
+// ------ ------
+//     Model
+// ------ ------
+
+/// The server has refused a change, because someone else has changed the movie in the meantime.
+pub struct Model {
+    movie_id: MovieId,
+    /// The local movie, including changes the server hasn't accepted.
+    mine: Movie,
+    /// The current movie on the server.
+    theirs: Movie,
+    /// The side to keep for each field with different values; local values are kept by default.
+    choices: BTreeMap<Field, Side>,
+}
+
+impl Model {
+    pub fn new(movie_id: MovieId, mine: Movie, theirs: Movie) -> Self {
+        Self { movie_id, mine, theirs, choices: BTreeMap::new() }
+    }
+
+    pub fn movie_id(&self) -> MovieId {
+        self.movie_id
+    }
+
+    pub fn theirs(&self) -> &Movie {
+        &self.theirs
+    }
+
+    fn differing_fields(&self) -> impl Iterator<Item = Field> + '_ {
+        Field::all().filter(move |field| field.text(&self.mine) != field.text(&self.theirs))
+    }
+
+    fn chosen_side(&self, field: Field) -> Side {
+        self.choices.get(&field).copied().unwrap_or(Side::Mine)
+    }
+
+    /// The server movie with the fields picked from the local one.
+    /// It has the server version, so the server accepts it as a change of its current movie.
+    pub fn resolved_movie(&self) -> Movie {
+        let mut movie = self.theirs.clone();
+        for field in self.differing_fields() {
+            if self.chosen_side(field) == Side::Mine {
+                field.copy(&self.mine, &mut movie);
+            }
+        }
+        movie
+    }
+
+    /// The local movie with the server version.
+    pub fn kept_movie(&self) -> Movie {
+        Movie { version: self.theirs.version, ..self.mine.clone() }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Side {
+    Mine,
+    Theirs,
+}
+
+/// Movie fields plus the ones edited outside of `MovieField` inputs.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Field {
+    Movie(MovieField),
+    Tags,
+    /// Watch status together with watch history.
+    WatchStatus,
+}
+
+impl Field {
+    fn all() -> impl Iterator<Item = Self> {
+        MovieField::ALL
+            .iter()
+            .copied()
+            .map(Self::Movie)
+            .chain(vec![Self::Tags, Self::WatchStatus])
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Movie(field) => field.label(),
+            Self::Tags => "Tags",
+            Self::WatchStatus => "Watch status",
+        }
+    }
+
+    fn text(self, movie: &Movie) -> String {
+        match self {
+            Self::Movie(field) => field.text(movie),
+            Self::Tags => movie.tags.join(", "),
+            Self::WatchStatus => {
+                let watched_on = movie
+                    .watch_history
+                    .iter()
+                    .map(|event| event.watched_on.format("%Y-%m-%d").to_string())
+                    .collect::<Vec<_>>();
+                if watched_on.is_empty() {
+                    movie.watch_status.label().to_owned()
+                } else {
+                    format!("{} ({})", movie.watch_status.label(), watched_on.join(", "))
+                }
+            },
+        }
+    }
+
+    fn copy(self, from: &Movie, to: &mut Movie) {
+        match self {
+            Self::Movie(field) => field.copy(from, to),
+            Self::Tags => to.tags = from.tags.clone(),
+            Self::WatchStatus => {
+                to.watch_status = from.watch_status;
+                to.watch_history = from.watch_history.clone();
+            },
+        }
+    }
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    SideChosen(Field, Side),
+
+    // ------ handled by the parent ------
+
+    KeepMine,
+    TakeTheirs,
+    SaveMerged,
+}
+
+pub fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::SideChosen(field, side) => {
+            model.choices.insert(field, side);
+        },
+
+        Msg::KeepMine | Msg::TakeTheirs | Msg::SaveMerged => {},
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+pub fn view(model: &Model) -> Node<Msg> {
+    let s_cell = || {
+        s()
+            .border("1px solid #dbdbdb")
+            .px(rem(0.5))
+            .py(rem(0.25))
+            .vertical_align(CssVerticalAlign::Top)
+    };
+    let s_button = || {
+        s()
+            .cursor(CssCursor::Pointer)
+            .mr(rem(0.5))
+    };
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .bg_color(rgba(10, 10, 10, 0.86))
+            .bottom("0")
+            .display(CssDisplay::Flex)
+            .justify_content(CssJustifyContent::Center)
+            .left("0")
+            .position(CssPosition::Fixed)
+            .right("0")
+            .top("0")
+            .z_index("40"),
+        div![
+            s()
+                .bg_color("white")
+                .border_radius(px(6))
+                .max_h("90vh")
+                .overflow_y("auto")
+                .p(rem(1.5))
+                .w(pc(90))
+                .max_w(px(900)),
+            h2![
+                s()
+                    .font_size(rem(1.5))
+                    .font_weight("600")
+                    .mb(rem(1)),
+                format!("\"{}\" was changed by someone else", model.theirs.title),
+            ],
+            p![
+                s()
+                    .color("#7a7a7a")
+                    .mb(rem(1)),
+                "Your changes haven't been saved. Keep yours, take the saved ones, or pick the value to keep for each field.",
+            ],
+            table![
+                s()
+                    .raw("border-collapse: collapse;")
+                    .w(pc(100)),
+                thead![
+                    tr![
+                        th![s_cell()],
+                        th![s_cell(), "Yours"],
+                        th![s_cell(), "Saved"],
+                    ]
+                ],
+                tbody![
+                    model.differing_fields().map(|field| {
+                        let chosen = model.chosen_side(field);
+                        tr![
+                            th![s_cell(), field.label()],
+                            vec![(Side::Mine, &model.mine), (Side::Theirs, &model.theirs)]
+                                .into_iter()
+                                .map(|(side, movie)| td![
+                                    s_cell(),
+                                    label![
+                                        input![
+                                            attrs!{
+                                                At::Type => "radio",
+                                                At::Name => field.label(),
+                                                At::Checked => (side == chosen).as_at_value(),
+                                            },
+                                            ev(Ev::Change, move |_| Msg::SideChosen(field, side)),
+                                        ],
+                                        " ",
+                                        match field.text(movie) {
+                                            text if text.is_empty() => em!["empty"],
+                                            text => span![text],
+                                        },
+                                    ]
+                                ]),
+                        ]
+                    })
+                ],
+            ],
+            div![
+                s()
+                    .mt(rem(1)),
+                button![
+                    C!["button"],
+                    s_button(),
+                    "Keep mine",
+                    ev(Ev::Click, |_| Msg::KeepMine),
+                ],
+                button![
+                    C!["button"],
+                    s_button(),
+                    "Take theirs",
+                    ev(Ev::Click, |_| Msg::TakeTheirs),
+                ],
+                button![
+                    C!["button"],
+                    s_button(),
+                    "Save selected values",
+                    ev(Ev::Click, |_| Msg::SaveMerged),
+                ],
+            ],
+        ]
+    ]
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+
+use super::{Movie, MovieId};
+
+const ARTICLES: [&str; 3] = ["the", "a", "an"];
+/// The highest `tolerance`.
+const MAX_TOLERANCE: usize = 3;
+
+/// Lowercase words without punctuation and leading articles,
+/// e.g. `"The Lord of the Rings: The Two Towers"` => `"lord of the rings the two towers"`.
+pub fn normalize_title(title: &str) -> String {
+    let title = title
+        .chars()
+        .map(|character| if character.is_alphanumeric() { character } else { ' ' })
+        .collect::<String>()
+        .to_lowercase();
+    let mut words = title.split_whitespace().peekable();
+    if words.peek().map_or(false, |word| ARTICLES.contains(word)) {
+        words.next();
+    }
+    words.collect::<Vec<_>>().join(" ")
+}
+
+/// Levenshtein distance in characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous_row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_row[j] + if a_char == *b_char { 0 } else { 1 };
+            row[j + 1] = substitution.min(previous_row[j + 1] + 1).min(row[j] + 1);
+        }
+        previous_row = row;
+    }
+    previous_row[b.len()]
+}
+
+/// Allowed typos grow with the title length; short titles have to match exactly.
+fn tolerance(normalized_title: &str) -> usize {
+    (normalized_title.chars().count() / 6).min(MAX_TOLERANCE)
+}
+
+/// Normalized title and year, prepared for repeated comparisons.
+struct Key {
+    title: String,
+    title_length: usize,
+    year: Option<i32>,
+}
+
+impl Key {
+    fn new(movie: &Movie) -> Self {
+        let title = normalize_title(&movie.title);
+        Self { title_length: title.chars().count(), title, year: movie.year }
+    }
+
+    /// A missing year matches any year.
+    fn matches(&self, other: &Key) -> bool {
+        if self.year.is_some() && other.year.is_some() && self.year != other.year {
+            return false;
+        }
+        let tolerance = tolerance(&self.title).min(tolerance(&other.title));
+        // The length difference is a lower bound of the edit distance and it's cheap.
+        let length_difference = if self.title_length > other.title_length {
+            self.title_length - other.title_length
+        } else {
+            other.title_length - self.title_length
+        };
+        length_difference <= tolerance && edit_distance(&self.title, &other.title) <= tolerance
+    }
+}
+
+pub fn is_likely_duplicate(a: &Movie, b: &Movie) -> bool {
+    Key::new(a).matches(&Key::new(b))
+}
+
+/// Existing movies that `movie` is likely a duplicate of.
+pub fn find_duplicates_of(movie: &Movie, movies: &BTreeMap<MovieId, Movie>) -> Vec<MovieId> {
+    let key = Key::new(movie);
+    movies
+        .iter()
+        .filter(|(_, movie)| key.matches(&Key::new(movie)))
+        .map(|(movie_id, _)| *movie_id)
+        .collect()
+}
+
+/// The first existing movie each of `new_movies` is likely a duplicate of.
+pub fn find_first_duplicates(new_movies: &[Movie], movies: &BTreeMap<MovieId, Movie>) -> Vec<Option<MovieId>> {
+    let keys = movies.iter().map(|(movie_id, movie)| (*movie_id, Key::new(movie))).collect::<Vec<_>>();
+    new_movies
+        .iter()
+        .map(|movie| {
+            let key = Key::new(movie);
+            keys.iter().find(|(_, other)| key.matches(other)).map(|(movie_id, _)| *movie_id)
+        })
+        .collect()
+}
+
+/// Pairs of indices into `keys` that may match - their years don't differ and their title lengths are close.
+/// Comparing every pair with the edit distance would freeze the page on large collections.
+/// Every pair is returned once, the lower index first.
+fn candidate_pairs(keys: &[Key]) -> Vec<(usize, usize)> {
+    let mut years = BTreeMap::<Option<i32>, Vec<usize>>::new();
+    for (index, key) in keys.iter().enumerate() {
+        years.entry(key.year).or_default().push(index);
+    }
+    let without_year = years.remove(&None).unwrap_or_default();
+
+    let mut pairs = Vec::new();
+    let mut add_pairs = |mut indices: Vec<usize>, is_candidate: &dyn Fn(usize, usize) -> bool| {
+        indices.sort_by_key(|index| keys[*index].title_length);
+        for (position, &i) in indices.iter().enumerate() {
+            for &j in &indices[position + 1..] {
+                // Sorted by length, so the remaining titles are too long to match.
+                if keys[j].title_length - keys[i].title_length > MAX_TOLERANCE {
+                    break;
+                }
+                if is_candidate(i, j) {
+                    pairs.push((i.min(j), i.max(j)));
+                }
+            }
+        }
+    };
+    // Movies without year match movies of any year.
+    for indices in years.into_values() {
+        let mut indices_with_unknown_year = indices;
+        indices_with_unknown_year.extend(&without_year);
+        add_pairs(indices_with_unknown_year, &|i, j| keys[i].year.is_some() || keys[j].year.is_some());
+    }
+    add_pairs(without_year, &|_, _| true);
+    pairs
+}
+
+/// Groups of likely duplicates in the whole collection; every group has at least two movies.
+/// A movie matching any movie in a group joins the group.
+pub fn find_duplicate_groups(movies: &BTreeMap<MovieId, Movie>) -> Vec<Vec<MovieId>> {
+    let (movie_ids, keys): (Vec<_>, Vec<_>) = movies
+        .iter()
+        .map(|(movie_id, movie)| (*movie_id, Key::new(movie)))
+        .unzip();
+
+    // Union-find over the indices.
+    fn root(parents: &mut [usize], mut index: usize) -> usize {
+        while parents[index] != index {
+            parents[index] = parents[parents[index]];
+            index = parents[index];
+        }
+        index
+    }
+    let mut parents = (0..keys.len()).collect::<Vec<_>>();
+    for (i, j) in candidate_pairs(&keys) {
+        if keys[i].matches(&keys[j]) {
+            let (root_i, root_j) = (root(&mut parents, i), root(&mut parents, j));
+            parents[root_j] = root_i;
+        }
+    }
+
+    let mut groups = BTreeMap::<usize, Vec<MovieId>>::new();
+    for (index, movie_id) in movie_ids.into_iter().enumerate() {
+        groups.entry(root(&mut parents, index)).or_default().push(movie_id);
+    }
+    groups.values().filter(|group| group.len() > 1).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures::collection;
+
+    fn movie(title: &str, year: Option<i32>) -> Movie {
+        Movie { title: title.to_owned(), year, ..Movie::default() }
+    }
+
+    #[test]
+    fn normalize_title_drops_case_punctuation_and_leading_article() {
+        assert_eq!(normalize_title("The Lord of the Rings: The Two Towers"), "lord of the rings the two towers");
+        assert_eq!(normalize_title("  A  Quiet Place "), "quiet place");
+        assert_eq!(normalize_title("Amélie"), "amélie");
+        assert_eq!(normalize_title("The"), "");
+        assert_eq!(normalize_title("Them!"), "them");
+    }
+
+    #[test]
+    fn edit_distance_counts_characters() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("alien", ""), 5);
+        assert_eq!(edit_distance("", "alien"), 5);
+        assert_eq!(edit_distance("alien", "alien"), 0);
+        assert_eq!(edit_distance("alien", "aliens"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("amélie", "amelie"), 1);
+    }
+
+    #[test]
+    fn tolerance_grows_with_length_up_to_the_max() {
+        assert_eq!(tolerance("alien"), 0);
+        assert_eq!(tolerance("jaws 2"), 1);
+        assert_eq!(tolerance("spirited away"), 2);
+        assert_eq!(tolerance("lord of the rings the two towers"), MAX_TOLERANCE);
+    }
+
+    #[test]
+    fn likely_duplicates_respect_year_and_tolerance() {
+        assert!(is_likely_duplicate(&movie("The Matrix", Some(1999)), &movie("Matrix", Some(1999))));
+        assert!(is_likely_duplicate(&movie("Spirited Away", None), &movie("Spirted Away", Some(2001))));
+        assert!(!is_likely_duplicate(&movie("Spirited Away", Some(2001)), &movie("Spirted Away", Some(2002))));
+        assert!(!is_likely_duplicate(&movie("Alien", None), &movie("Aliens", None)));
+    }
+
+    #[test]
+    fn duplicate_groups_join_transitive_matches_only() {
+        let (movie_ids, movies) = collection(vec![
+            movie("Seven Samurai", Some(1954)),
+            movie("Alien", Some(1979)),
+            movie("Seven Samurais", None),
+            movie("Sevn Samurai", Some(1954)),
+            movie("Seven Samurai", Some(1960)),
+            movie("Aliens", Some(1986)),
+        ]);
+        assert_eq!(find_duplicate_groups(&movies), vec![vec![movie_ids[0], movie_ids[2], movie_ids[3], movie_ids[4]]]);
+    }
+
+    #[test]
+    fn duplicate_groups_match_titles_without_year() {
+        let (movie_ids, movies) = collection(vec![
+            movie("Spirited Away", None),
+            movie("Spirited Away!", None),
+            movie("Princess Mononoke", None),
+        ]);
+        assert_eq!(find_duplicate_groups(&movies), vec![vec![movie_ids[0], movie_ids[1]]]);
+    }
+
+    #[test]
+    fn first_duplicates_of_new_movies() {
+        let (movie_ids, movies) = collection(vec![movie("Alien", Some(1979)), movie("Matrix", None)]);
+        let new_movies = vec![movie("The Matrix", Some(1999)), movie("Aliens", Some(1986)), movie("alien", None)];
+        assert_eq!(find_first_duplicates(&new_movies, &movies), vec![Some(movie_ids[1]), None, Some(movie_ids[0])]);
+    }
+}
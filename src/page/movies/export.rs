@@ -0,0 +1,185 @@
+//! Serializers for movie exports. They don't depend on the browser, so they work on native targets too.
+
+use serde::Serialize;
+
+use super::movie::{added_at, format_runtime, Movie, MovieId};
+use super::rating::format_stars;
+
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 3] = [Self::Csv, Self::Json, Self::Markdown];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Json => "JSON",
+            Self::Markdown => "Markdown",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Csv => "movies.csv",
+            Self::Json => "movies.json",
+            Self::Markdown => "movies.md",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Json => "application/json",
+            Self::Markdown => "text/markdown",
+        }
+    }
+
+    pub fn export(self, movies: &[(&MovieId, &Movie)]) -> Result<String, String> {
+        match self {
+            Self::Csv => to_csv(movies).map_err(|error| error.to_string()),
+            Self::Json => to_json(movies).map_err(|error| error.to_string()),
+            Self::Markdown => Ok(to_markdown(movies)),
+        }
+    }
+}
+
+// ------ CSV ------
+
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    id: String,
+    title: &'a str,
+    year: Option<i32>,
+    genres: String,
+    tags: String,
+    cast: String,
+    crew: String,
+    runtime: Option<u32>,
+    rating: Option<String>,
+    status: &'static str,
+    watch_count: usize,
+    last_watched_on: Option<String>,
+    poster_url: Option<&'a str>,
+    synopsis: Option<&'a str>,
+    review: Option<&'a str>,
+    added_at: String,
+}
+
+/// Column names and values match the CSV importer, so exported files can be imported back.
+/// Only the id, the watch history and the date added are for information and aren't imported.
+pub fn to_csv(movies: &[(&MovieId, &Movie)]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for (movie_id, movie) in movies {
+        writer.serialize(CsvRow {
+            id: movie_id.to_string(),
+            title: &movie.title,
+            year: movie.year,
+            genres: movie.genres.join(", "),
+            tags: movie.tags.join(", "),
+            cast: movie.cast.join(", "),
+            crew: movie.crew.join(", "),
+            runtime: movie.runtime,
+            rating: movie.rating.map(format_stars),
+            status: movie.watch_status.as_str(),
+            watch_count: movie.watch_history.len(),
+            last_watched_on: movie.last_watched_on().map(|date| date.format("%Y-%m-%d").to_string()),
+            poster_url: movie.poster_url.as_deref(),
+            synopsis: movie.synopsis.as_deref(),
+            review: movie.review.as_ref().map(|review| review.text.as_str()),
+            added_at: added_at(**movie_id).to_rfc3339(),
+        })?;
+    }
+    let bytes = writer.into_inner().map_err(|error| error.into_error())?;
+    Ok(String::from_utf8(bytes).expect("CSV writer produces UTF-8 from UTF-8 input"))
+}
+
+// ------ JSON ------
+
+#[derive(Serialize)]
+struct JsonMovie<'a> {
+    id: MovieId,
+    #[serde(flatten)]
+    movie: &'a Movie,
+}
+
+pub fn to_json(movies: &[(&MovieId, &Movie)]) -> serde_json::Result<String> {
+    let movies = movies
+        .iter()
+        .map(|(movie_id, movie)| JsonMovie { id: **movie_id, movie })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&movies)
+}
+
+// ------ Markdown ------
+
+pub fn to_markdown(movies: &[(&MovieId, &Movie)]) -> String {
+    let mut markdown = String::from(
+        "| Title | Year | Genres | Runtime | Rating | Status |\n\
+         | --- | --- | --- | --- | --- | --- |\n"
+    );
+    for (_, movie) in movies {
+        let cells = [
+            movie.title.clone(),
+            movie.year.map(|year| year.to_string()).unwrap_or_default(),
+            movie.genres.join(", "),
+            movie.runtime.map(format_runtime).unwrap_or_default(),
+            movie.rating.map(|rating| format!("★ {}", format_stars(rating))).unwrap_or_default(),
+            movie.watch_status.label().to_owned(),
+        ];
+        let cells = cells.iter().map(|cell| escape_markdown_cell(cell)).collect::<Vec<_>>();
+        markdown.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    markdown
+}
+
+fn escape_markdown_cell(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movie_id() -> MovieId {
+        MovieId::from_string("01E5ZJ8Q0M6N8X4Q7S9T2V3W4Y").unwrap()
+    }
+
+    fn movie(title: &str) -> Movie {
+        Movie { title: title.to_owned(), ..Movie::default() }
+    }
+
+    #[test]
+    fn csv_quotes_commas_quotes_and_newlines() {
+        let movie = Movie {
+            synopsis: Some("First line\nsecond line".to_owned()),
+            ..movie("Crouching Tiger, Hidden \"Dragon\"")
+        };
+        let csv = to_csv(&[(&movie_id(), &movie)]).unwrap();
+        assert!(csv.contains(r#""Crouching Tiger, Hidden ""Dragon""""#));
+        assert!(csv.contains("\"First line\nsecond line\""));
+    }
+
+    #[test]
+    fn json_contains_the_id_and_movie_fields() {
+        let json = to_json(&[(&movie_id(), &movie("Alien"))]).unwrap();
+        let movies = serde_json::from_str::<Vec<serde_json::Value>>(&json).unwrap();
+        assert_eq!(movies.len(), 1);
+        assert_eq!(movies[0]["id"], "01E5ZJ8Q0M6N8X4Q7S9T2V3W4Y");
+        assert_eq!(movies[0]["title"], "Alien");
+    }
+
+    #[test]
+    fn markdown_escapes_pipes_backslashes_and_newlines() {
+        assert_eq!(escape_markdown_cell("A | B \\ C\nD"), "A \\| B \\\\ C D");
+
+        let markdown = to_markdown(&[(&movie_id(), &Movie { runtime: Some(95), ..movie("Love | Death") })]);
+        let lines = markdown.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2], "| Love \\| Death |  |  | 1h 35min |  | Want to watch |");
+    }
+}
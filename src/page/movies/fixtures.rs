@@ -0,0 +1,16 @@
+//! Movie collections for the tests of the movie modules and the home page stats.
+
+use std::collections::BTreeMap;
+
+use super::{Movie, MovieId};
+
+/// The movies with ids 1, 2, 3, ... in the given order.
+pub fn collection(movies: Vec<Movie>) -> (Vec<MovieId>, BTreeMap<MovieId, Movie>) {
+    let movie_ids = (1..=movies.len() as u128).map(MovieId::from).collect::<Vec<_>>();
+    (movie_ids.clone(), movie_ids.into_iter().zip(movies).collect())
+}
+
+/// Default movies with the given ids, e.g. ids created on given days.
+pub fn default_movies(movie_ids: &[MovieId]) -> BTreeMap<MovieId, Movie> {
+    movie_ids.iter().map(|movie_id| (*movie_id, Movie::default())).collect()
+}
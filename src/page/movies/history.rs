@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use super::{Movie, MovieId};
+
+/// Older changes are forgotten.
+const MAX_CHANGES: usize = 100;
+
+/// A movie's state before and after a change; `None` means the movie doesn't exist.
+#[derive(Clone)]
+pub struct Step {
+    pub movie_id: MovieId,
+    pub from: Option<Movie>,
+    pub to: Option<Movie>,
+}
+
+/// One user action, e.g. an edit or a bulk delete.
+struct Change {
+    label: String,
+    steps: Vec<Step>,
+}
+
+impl Change {
+    fn reversed(&self) -> Vec<Step> {
+        self.steps
+            .iter()
+            .map(|step| Step { movie_id: step.movie_id, from: step.to.clone(), to: step.from.clone() })
+            .collect()
+    }
+}
+
+/// Undo and redo stacks of movie changes.
+/// The history doesn't change the movies, it only returns the steps to apply.
+#[derive(Default)]
+pub struct History {
+    undo_stack: Vec<Change>,
+    redo_stack: Vec<Change>,
+}
+
+impl History {
+    /// `before` contains the affected movies as they were before the change,
+    /// `movies` is the collection after the change. Unchanged movies are ignored.
+    /// Returns `false` if nothing has changed.
+    pub fn record(
+        &mut self,
+        label: impl Into<String>,
+        before: Vec<(MovieId, Option<Movie>)>,
+        movies: &BTreeMap<MovieId, Movie>,
+    ) -> bool {
+        let steps = before
+            .into_iter()
+            .map(|(movie_id, from)| Step { movie_id, from, to: movies.get(&movie_id).cloned() })
+            .filter(|step| step.from != step.to)
+            .collect::<Vec<_>>();
+        if steps.is_empty() {
+            return false;
+        }
+        self.undo_stack.push(Change { label: label.into(), steps });
+        if self.undo_stack.len() > MAX_CHANGES {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+        true
+    }
+
+    pub fn undo(&mut self) -> Option<Vec<Step>> {
+        let change = self.undo_stack.pop()?;
+        let steps = change.reversed();
+        self.redo_stack.push(change);
+        Some(steps)
+    }
+
+    pub fn redo(&mut self) -> Option<Vec<Step>> {
+        let change = self.redo_stack.pop()?;
+        let steps = change.steps.clone();
+        self.undo_stack.push(change);
+        Some(steps)
+    }
+
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo_stack.last().map(|change| change.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo_stack.last().map(|change| change.label.as_str())
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movie(title: &str) -> Movie {
+        Movie { title: title.to_owned(), ..Movie::default() }
+    }
+
+    fn titles(movies: &BTreeMap<MovieId, Movie>) -> Vec<&str> {
+        movies.values().map(|movie| movie.title.as_str()).collect()
+    }
+
+    /// Applies the steps like the movies page does.
+    fn apply(movies: &mut BTreeMap<MovieId, Movie>, steps: Vec<Step>) {
+        for Step { movie_id, to, .. } in steps {
+            match to {
+                Some(movie) => movies.insert(movie_id, movie),
+                None => movies.remove(&movie_id),
+            };
+        }
+    }
+
+    /// Renames the movie and records the change.
+    fn rename(history: &mut History, movies: &mut BTreeMap<MovieId, Movie>, movie_id: MovieId, title: &str) {
+        let before = vec![(movie_id, movies.get(&movie_id).cloned())];
+        movies.insert(movie_id, movie(title));
+        assert!(history.record(format!("Rename to {}", title), before, movies));
+    }
+
+    #[test]
+    fn unchanged_movies_are_not_recorded() {
+        let mut history = History::default();
+        let movie_id = MovieId::from(1);
+        let movies = vec![(movie_id, movie("Alien"))].into_iter().collect::<BTreeMap<_, _>>();
+
+        assert!(!history.record("Edit title", vec![(movie_id, Some(movie("Alien")))], &movies));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn undo_after_redo_restores_the_previous_state_again() {
+        let mut history = History::default();
+        let movie_id = MovieId::from(1);
+        let mut movies = vec![(movie_id, movie("Alien"))].into_iter().collect::<BTreeMap<_, _>>();
+        rename(&mut history, &mut movies, movie_id, "Aliens");
+
+        apply(&mut movies, history.undo().unwrap());
+        assert_eq!(titles(&movies), vec!["Alien"]);
+        apply(&mut movies, history.redo().unwrap());
+        assert_eq!(titles(&movies), vec!["Aliens"]);
+        assert_eq!(history.undo_label(), Some("Rename to Aliens"));
+        assert_eq!(history.redo_label(), None);
+
+        apply(&mut movies, history.undo().unwrap());
+        assert_eq!(titles(&movies), vec!["Alien"]);
+        assert_eq!(history.redo_label(), Some("Rename to Aliens"));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn undo_of_a_deletion_recreates_the_movie() {
+        let mut history = History::default();
+        let movie_id = MovieId::from(1);
+        let mut movies = vec![(movie_id, movie("Alien"))].into_iter().collect::<BTreeMap<_, _>>();
+        let removed_movie = movies.remove(&movie_id);
+        assert!(history.record("Delete \"Alien\"", vec![(movie_id, removed_movie)], &movies));
+
+        apply(&mut movies, history.undo().unwrap());
+        assert_eq!(titles(&movies), vec!["Alien"]);
+        apply(&mut movies, history.redo().unwrap());
+        assert!(movies.is_empty());
+    }
+
+    #[test]
+    fn new_change_clears_the_redo_stack() {
+        let mut history = History::default();
+        let movie_id = MovieId::from(1);
+        let mut movies = vec![(movie_id, movie("Alien"))].into_iter().collect::<BTreeMap<_, _>>();
+        rename(&mut history, &mut movies, movie_id, "Aliens");
+        apply(&mut movies, history.undo().unwrap());
+        assert_eq!(history.redo_label(), Some("Rename to Aliens"));
+
+        rename(&mut history, &mut movies, movie_id, "Alien 3");
+
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo_label(), Some("Rename to Alien 3"));
+    }
+
+    #[test]
+    fn oldest_changes_are_forgotten() {
+        let mut history = History::default();
+        let movie_id = MovieId::from(1);
+        let mut movies = vec![(movie_id, movie("Title 0"))].into_iter().collect::<BTreeMap<_, _>>();
+        for index in 1..=MAX_CHANGES + 1 {
+            rename(&mut history, &mut movies, movie_id, &format!("Title {}", index));
+        }
+
+        let mut undone_changes = 0;
+        while let Some(steps) = history.undo() {
+            apply(&mut movies, steps);
+            undone_changes += 1;
+        }
+
+        assert_eq!(undone_changes, MAX_CHANGES);
+        // The first rename has been forgotten.
+        assert_eq!(titles(&movies), vec!["Title 1"]);
+        assert_eq!(history.redo_label(), Some("Rename to Title 2"));
+    }
+
+    #[test]
+    fn clear_forgets_both_stacks() {
+        let mut history = History::default();
+        let movie_id = MovieId::from(1);
+        let mut movies = vec![(movie_id, movie("Alien"))].into_iter().collect::<BTreeMap<_, _>>();
+        rename(&mut history, &mut movies, movie_id, "Aliens");
+        rename(&mut history, &mut movies, movie_id, "Alien 3");
+        history.undo();
+
+        history.clear();
+
+        assert!(history.undo().is_none());
+        assert!(history.redo().is_none());
+    }
+}
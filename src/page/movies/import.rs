@@ -0,0 +1,486 @@
+use seed::{prelude::*, *};
+
+use seed_style::{pc, px, rem};
+use seed_style::*;
+
+use chrono::NaiveDate;
+use wasm_bindgen_futures::JsFuture;
+
+use super::{parse_list, Movie, MovieField, WatchStatus};
+
+mod imdb;
+mod letterboxd;
+
+/// Only the first rows are rendered in the preview, but all rows are imported.
+const PREVIEW_ROWS: usize = 50;
+
+// ------ ------
+//     Model
+// ------ ------
+
+#[derive(Default)]
+pub struct Model {
+    file_name: Option<String>,
+    source: Source,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    /// Target field for each CSV column; `None` means the column is skipped.
+    mapping: Vec<Option<Column>>,
+    error: Option<String>,
+}
+
+impl Model {
+    pub fn parsed_rows(&self) -> impl Iterator<Item = Result<Movie, Vec<String>>> + '_ {
+        self.rows.iter().map(move |row| match self.source {
+            Source::Generic => row_to_movie(row, &self.mapping),
+            Source::Letterboxd => letterboxd::row_to_movie(&self.headers, row),
+            Source::LetterboxdWatchlist => letterboxd::watchlist_row_to_movie(&self.headers, row),
+            Source::Imdb => imdb::row_to_movie(&self.headers, row),
+        })
+    }
+
+    pub fn valid_movies(&self) -> Vec<Movie> {
+        self.parsed_rows().filter_map(Result::ok).collect()
+    }
+}
+
+/// Letterboxd and IMDb exports have well-known columns, so they don't need a column mapping.
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    Generic,
+    Letterboxd,
+    LetterboxdWatchlist,
+    Imdb,
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Self::Generic
+    }
+}
+
+impl Source {
+    fn detect(headers: &[String], file_name: Option<&str>) -> Self {
+        if letterboxd::matches(headers) {
+            if file_name.map_or(false, letterboxd::is_watchlist) {
+                Self::LetterboxdWatchlist
+            } else {
+                Self::Letterboxd
+            }
+        } else if imdb::matches(headers) {
+            Self::Imdb
+        } else {
+            Self::Generic
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Generic => "CSV",
+            Self::Letterboxd => "Letterboxd export",
+            Self::LetterboxdWatchlist => "Letterboxd watchlist",
+            Self::Imdb => "IMDb export",
+        }
+    }
+}
+
+/// Import target of a CSV column: a movie field, or a value edited outside of `MovieField` inputs.
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    Movie(MovieField),
+    Tags,
+    WatchStatus,
+}
+
+impl Column {
+    fn all() -> Vec<Self> {
+        MovieField::ALL
+            .iter()
+            .copied()
+            .map(Self::Movie)
+            .chain(vec![Self::Tags, Self::WatchStatus])
+            .collect()
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Movie(field) => field.label(),
+            Self::Tags => "Tags",
+            Self::WatchStatus => "Watch status",
+        }
+    }
+
+    fn set(self, movie: &mut Movie, text: &str) -> Result<(), String> {
+        match self {
+            Self::Movie(field) => return field.set(movie, text),
+            Self::Tags => movie.tags = parse_list(text),
+            Self::WatchStatus => movie.watch_status = parse_watch_status(text)?,
+        }
+        Ok(())
+    }
+}
+
+/// The value (`want_to_watch`) or the label (`Want to watch`); empty cells keep the default status.
+fn parse_watch_status(text: &str) -> Result<WatchStatus, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(WatchStatus::default());
+    }
+    WatchStatus::ALL
+        .iter()
+        .copied()
+        .find(|status| status.as_str() == text || status.label().eq_ignore_ascii_case(text))
+        .ok_or_else(|| format!("Unknown watch status \"{}\".", text))
+}
+
+fn row_to_movie(row: &[String], mapping: &[Option<Column>]) -> Result<Movie, Vec<String>> {
+    if not(mapping.contains(&Some(Column::Movie(MovieField::Title)))) {
+        return Err(vec!["Map a column to Title.".to_owned()]);
+    }
+    let mut movie = Movie::default();
+    // Rows of flexible CSV files may be shorter than the header; missing cells are empty.
+    let errors = mapping
+        .iter()
+        .enumerate()
+        .filter_map(|(index, field)| field.map(|field| (field, row.get(index).map_or("", String::as_str))))
+        .filter_map(|(field, value)| field.set(&mut movie, value).err())
+        .collect::<Vec<_>>();
+
+    if errors.is_empty() { Ok(movie) } else { Err(errors) }
+}
+
+/// Guesses the target field from the CSV header.
+fn default_field(header: &str) -> Option<Column> {
+    let field = match header.trim().to_lowercase().as_str() {
+        "title" | "name" => MovieField::Title,
+        "year" | "release year" => MovieField::Year,
+        "genre" | "genres" => MovieField::Genres,
+        "cast" | "actors" => MovieField::Cast,
+        "crew" | "director" | "directors" => MovieField::Crew,
+        "runtime" | "minutes" | "length" => MovieField::Runtime,
+        "rating" | "stars" => MovieField::Rating,
+        "poster" | "poster url" | "poster_url" => MovieField::PosterUrl,
+        "synopsis" | "description" | "plot" => MovieField::Synopsis,
+        "review" => MovieField::Review,
+        "tag" | "tags" => return Some(Column::Tags),
+        "status" | "watch status" | "watch_status" => return Some(Column::WatchStatus),
+        _ => return None,
+    };
+    Some(Column::Movie(field))
+}
+
+fn has_column(headers: &[String], name: &str) -> bool {
+    headers.iter().any(|header| header == name)
+}
+
+/// The trimmed value in the column with the given header; empty cells are `None`.
+fn cell<'a>(headers: &[String], row: &'a [String], name: &str) -> Option<&'a str> {
+    let column = headers.iter().position(|header| header == name)?;
+    row.get(column).map(|value| value.trim()).filter(|value| not(value.is_empty()))
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| format!("Invalid date \"{}\".", text))
+}
+
+pub fn parse_csv(text: &str) -> Result<(Vec<String>, Vec<Vec<String>>), csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+
+    let headers = reader.headers()?.iter().map(ToOwned::to_owned).collect();
+    let rows = reader
+        .records()
+        .map(|record| record.map(|record| record.iter().map(ToOwned::to_owned).collect()))
+        .collect::<Result<_, _>>()?;
+    Ok((headers, rows))
+}
+
+async fn read_file(file: web_sys::File) -> Result<String, String> {
+    JsFuture::from(file.text())
+        .await
+        .map_err(|error| format!("Cannot read the file: {:?}", error))?
+        .as_string()
+        .ok_or_else(|| "The file doesn't contain text.".to_owned())
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    FileSelected(Option<web_sys::File>),
+    FileRead(Result<String, String>),
+    ColumnMappingChanged(usize, String),
+
+    // ------ handled by the parent ------
+
+    Import,
+    Close,
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::FileSelected(Some(file)) => {
+            model.file_name = Some(file.name());
+            orders.perform_cmd(async move { Msg::FileRead(read_file(file).await) });
+        },
+        Msg::FileSelected(None) => {},
+        Msg::FileRead(Ok(text)) => match parse_csv(&text) {
+            Ok((headers, rows)) => {
+                model.source = Source::detect(&headers, model.file_name.as_deref());
+                model.mapping = headers.iter().map(|header| default_field(header)).collect();
+                model.headers = headers;
+                model.rows = rows;
+                model.error = None;
+            },
+            Err(error) => model.error = Some(format!("Invalid CSV: {}", error)),
+        },
+        Msg::FileRead(Err(error)) => model.error = Some(error),
+        Msg::ColumnMappingChanged(column, field) => {
+            if let Some(mapping) = model.mapping.get_mut(column) {
+                *mapping = field.parse::<usize>().ok().and_then(|index| Column::all().get(index).copied());
+            }
+        },
+
+        Msg::Import | Msg::Close => {},
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .bg_color(rgba(10, 10, 10, 0.86))
+            .bottom("0")
+            .display(CssDisplay::Flex)
+            .justify_content(CssJustifyContent::Center)
+            .left("0")
+            .position(CssPosition::Fixed)
+            .right("0")
+            .top("0")
+            .z_index("40"),
+        div![
+            s()
+                .bg_color("white")
+                .border_radius(px(6))
+                .max_h("90vh")
+                .overflow_y("auto")
+                .p(rem(1.5))
+                .w(pc(90))
+                .max_w(px(1100)),
+            h2![
+                s()
+                    .font_size(rem(1.5))
+                    .font_weight("600")
+                    .mb(rem(1)),
+                "Import movies from CSV",
+            ],
+            p![
+                s()
+                    .color("#7a7a7a")
+                    .mb(rem(1)),
+                "Letterboxd (watched.csv, ratings.csv, diary.csv) and IMDb list exports are recognized automatically. \
+                Movies already in your collection are merged instead of added again.",
+            ],
+            input![
+                attrs!{
+                    At::Type => "file",
+                    At::Accept => ".csv,text/csv",
+                },
+                ev(Ev::Change, |event| {
+                    let input = event
+                        .target()
+                        .and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok());
+                    Msg::FileSelected(input.and_then(|input| input.files()).and_then(|files| files.get(0)))
+                }),
+            ],
+            model.error.as_ref().map(|error| p![
+                s()
+                    .color("#f14668")
+                    .mt(rem(0.5)),
+                error
+            ]),
+            IF!(not(model.headers.is_empty()) => view_preview(model)),
+            view_buttons(model),
+        ]
+    ]
+}
+
+fn view_preview(model: &Model) -> Node<Msg> {
+    let s_cell = || {
+        s()
+            .border("1px solid #dbdbdb")
+            .px(rem(0.5))
+            .py(rem(0.25))
+            .vertical_align(CssVerticalAlign::Top)
+    };
+    div![
+        s()
+            .mt(rem(1))
+            .overflow_x("auto"),
+        p![
+            s()
+                .font_weight("600")
+                .mb(rem(0.5)),
+            format!("Detected format: {}", model.source.label()),
+        ],
+        table![
+            s()
+                .font_size(rem(0.875))
+                .raw("border-collapse: collapse;"),
+            thead![
+                tr![
+                    model.headers.iter().zip(&model.mapping).enumerate().map(|(column, (header, field))| {
+                        th![
+                            s_cell(),
+                            div![header],
+                            IF!(model.source == Source::Generic => select![
+                                option![
+                                    attrs!{At::Value => ""},
+                                    "— skip —",
+                                ],
+                                Column::all().into_iter().enumerate().map(|(index, option_field)| option![
+                                    attrs!{
+                                        At::Value => index,
+                                        At::Selected => (*field == Some(option_field)).as_at_value(),
+                                    },
+                                    option_field.label(),
+                                ]),
+                                input_ev(Ev::Change, move |field| Msg::ColumnMappingChanged(column, field)),
+                            ]),
+                        ]
+                    }),
+                    th![s_cell(), "Validation"],
+                ]
+            ],
+            tbody![
+                model.rows.iter().zip(model.parsed_rows()).take(PREVIEW_ROWS).map(|(row, parsed)| {
+                    tr![
+                        row.iter().map(|value| td![s_cell(), value]),
+                        match parsed {
+                            Ok(_) => td![s_cell(), s().color("#48c774"), "✓"],
+                            Err(errors) => td![s_cell(), s().color("#f14668"), errors.join(" ")],
+                        },
+                    ]
+                })
+            ],
+        ],
+        IF!(model.rows.len() > PREVIEW_ROWS => p![
+            s()
+                .color("#7a7a7a")
+                .mt(rem(0.5)),
+            format!("… and {} more rows.", model.rows.len() - PREVIEW_ROWS)
+        ]),
+    ]
+}
+
+fn view_buttons(model: &Model) -> Node<Msg> {
+    let valid_count = model.parsed_rows().filter(Result::is_ok).count();
+    div![
+        s()
+            .mt(rem(1)),
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .mr(rem(0.5)),
+            attrs!{At::Disabled => (valid_count == 0).as_at_value()},
+            format!("Import {} movies", valid_count),
+            ev(Ev::Click, |_| Msg::Import),
+        ],
+        button![
+            C!["button"],
+            s()
+                .cursor(CssCursor::Pointer),
+            "Cancel",
+            ev(Ev::Click, |_| Msg::Close),
+        ],
+        IF!(valid_count < model.rows.len() => span![
+            s()
+                .color("#7a7a7a")
+                .ml(rem(0.75)),
+            format!("{} invalid rows will be skipped.", model.rows.len() - valid_count)
+        ]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::export::to_csv;
+    use super::super::MovieId;
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| (*cell).to_owned()).collect()
+    }
+
+    #[test]
+    fn mapped_row_is_imported() {
+        let mapping = vec![Some(Column::Movie(MovieField::Year)), None, Some(Column::Movie(MovieField::Title))];
+        let movie = row_to_movie(&row(&["1999", "ignored", "The Matrix"]), &mapping).ok().unwrap();
+        assert_eq!(movie.title, "The Matrix");
+        assert_eq!(movie.year, Some(1999));
+    }
+
+    #[test]
+    fn short_row_without_title_is_refused() {
+        let mapping = vec![Some(Column::Movie(MovieField::Year)), Some(Column::Movie(MovieField::Title))];
+        let errors = row_to_movie(&row(&["1999"]), &mapping).err();
+        assert_eq!(errors, Some(vec!["Title is required.".to_owned()]));
+    }
+
+    #[test]
+    fn short_row_with_title_is_imported() {
+        let mapping = vec![Some(Column::Movie(MovieField::Title)), Some(Column::Movie(MovieField::Year))];
+        let movie = row_to_movie(&row(&["Alien"]), &mapping).ok().unwrap();
+        assert_eq!(movie.title, "Alien");
+        assert_eq!(movie.year, None);
+    }
+
+    #[test]
+    fn mapping_without_title_is_refused() {
+        let errors = row_to_movie(&row(&["1999"]), &[Some(Column::Movie(MovieField::Year))]).err();
+        assert_eq!(errors, Some(vec!["Map a column to Title.".to_owned()]));
+    }
+
+    #[test]
+    fn tags_and_watch_status_are_imported() {
+        let mapping = vec![Some(Column::Movie(MovieField::Title)), Some(Column::Tags), Some(Column::WatchStatus)];
+        let movie = row_to_movie(&row(&["Alien", "space, horror", "Watched"]), &mapping).ok().unwrap();
+        assert_eq!(movie.tags, vec!["space".to_owned(), "horror".to_owned()]);
+        assert!(movie.watch_status == WatchStatus::Watched);
+
+        let errors = row_to_movie(&row(&["Alien", "", "seen"]), &mapping).err();
+        assert_eq!(errors, Some(vec!["Unknown watch status \"seen\".".to_owned()]));
+    }
+
+    #[test]
+    fn exported_csv_is_imported_back() {
+        let movie = Movie {
+            title: "Crouching Tiger, Hidden \"Dragon\"".to_owned(),
+            year: Some(2000),
+            genres: vec!["Action".to_owned(), "Drama".to_owned()],
+            tags: vec!["wuxia".to_owned()],
+            cast: vec!["Chow Yun-fat".to_owned(), "Michelle Yeoh".to_owned()],
+            crew: vec!["Ang Lee".to_owned()],
+            runtime: Some(120),
+            rating: Some(7),
+            poster_url: Some("https://example.com/poster.jpg".to_owned()),
+            synopsis: Some("First line\nsecond line".to_owned()),
+            watch_status: WatchStatus::Abandoned,
+            ..Movie::default()
+        };
+        let csv = to_csv(&[(&MovieId::from(1), &movie)]).unwrap();
+
+        let (headers, rows) = parse_csv(&csv).unwrap();
+        let mapping = headers.iter().map(|header| default_field(header)).collect::<Vec<_>>();
+        assert_eq!(rows.len(), 1);
+        assert!(row_to_movie(&rows[0], &mapping).ok() == Some(movie));
+    }
+}
@@ -0,0 +1,135 @@
+use seed::prelude::*;
+
+use super::{cell, has_column, parse_date};
+use super::super::{parse_list, parse_runtime, parse_title, parse_year, Movie, WatchEvent, WatchStatus};
+
+/// IMDb list and ratings exports identify titles by their `Const` id (e.g. `tt0111161`).
+pub fn matches(headers: &[String]) -> bool {
+    has_column(headers, "Const") && has_column(headers, "Title Type")
+}
+
+pub fn row_to_movie(headers: &[String], row: &[String]) -> Result<Movie, Vec<String>> {
+    if let Some(title_type) = cell(headers, row, "Title Type") {
+        if not(is_movie(title_type)) {
+            return Err(vec![format!("Not a movie ({}).", title_type)]);
+        }
+    }
+    let title = parse_title(cell(headers, row, "Title").unwrap_or_default());
+    let year = parse_year(cell(headers, row, "Year").unwrap_or_default());
+    let runtime = parse_runtime(cell(headers, row, "Runtime (mins)").unwrap_or_default());
+    let genres = parse_list(cell(headers, row, "Genres").unwrap_or_default());
+    let crew = parse_list(cell(headers, row, "Directors").unwrap_or_default());
+    let rating = cell(headers, row, "Your Rating").map(parse_rating).transpose();
+    // IMDb doesn't export watch dates, the rating date is the closest approximation.
+    let watched_on = cell(headers, row, "Date Rated").map(parse_date).transpose();
+
+    match (title, year, runtime, rating, watched_on) {
+        (Ok(title), Ok(year), Ok(runtime), Ok(rating), Ok(watched_on)) => Ok(Movie {
+            title,
+            year,
+            runtime,
+            genres,
+            crew,
+            rating,
+            // Rated titles have been watched; the rest is a watchlist.
+            watch_status: if rating.is_some() { WatchStatus::Watched } else { WatchStatus::WantToWatch },
+            watch_history: watched_on
+                .filter(|_| rating.is_some())
+                .map(|watched_on| WatchEvent { watched_on })
+                .into_iter()
+                .collect(),
+            ..Movie::default()
+        }),
+        (title, year, runtime, rating, watched_on) => Err(
+            vec![title.err(), year.err(), runtime.err(), rating.err(), watched_on.err()]
+                .into_iter()
+                .flatten()
+                .collect()
+        ),
+    }
+}
+
+/// E.g. `Movie`, `TV Movie`, `Short` or `Video`, but not `TV Series` or `TV Episode`.
+fn is_movie(title_type: &str) -> bool {
+    let title_type = title_type.to_lowercase();
+    not(["series", "episode", "game"].iter().any(|excluded| title_type.contains(excluded)))
+}
+
+/// IMDb rates from 1 to 10, which maps exactly onto half-stars.
+fn parse_rating(text: &str) -> Result<u8, String> {
+    match text.parse::<u8>() {
+        Ok(rating) if (1..=10).contains(&rating) => Ok(rating),
+        _ => Err("IMDb rating has to be a whole number between 1 and 10.".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const HEADERS: [&str; 13] = [
+        "Const", "Your Rating", "Date Rated", "Title", "URL", "Title Type", "IMDb Rating", "Runtime (mins)", "Year",
+        "Genres", "Num Votes", "Release Date", "Directors",
+    ];
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| (*cell).to_owned()).collect()
+    }
+
+    /// A row with the given rating, rating date and title type.
+    fn title_row(rating: &str, date_rated: &str, title_type: &str) -> Vec<String> {
+        row(&[
+            "tt0080684", rating, date_rated, "The Empire Strikes Back", "https://www.imdb.com/title/tt0080684/",
+            title_type, "8.7", "124", "1980", "Action, Adventure, Fantasy", "1200000", "1980-05-17",
+            "Irvin Kershner",
+        ])
+    }
+
+    #[test]
+    fn exports_are_detected_by_their_columns() {
+        assert!(matches(&row(&HEADERS)));
+        assert!(not(matches(&row(&["Const", "Title"]))));
+    }
+
+    #[test]
+    fn rated_movie_is_watched_on_the_rating_date() {
+        let movie = row_to_movie(&row(&HEADERS), &title_row("8", "2020-05-04", "Movie"));
+        let expected = Movie {
+            title: "The Empire Strikes Back".to_owned(),
+            year: Some(1980),
+            runtime: Some(124),
+            genres: vec!["Action".to_owned(), "Adventure".to_owned(), "Fantasy".to_owned()],
+            crew: vec!["Irvin Kershner".to_owned()],
+            rating: Some(8),
+            watch_status: WatchStatus::Watched,
+            watch_history: vec![WatchEvent { watched_on: NaiveDate::from_ymd(2020, 5, 4) }],
+            ..Movie::default()
+        };
+        assert!(movie.ok() == Some(expected));
+    }
+
+    #[test]
+    fn unrated_movie_is_want_to_watch() {
+        let movie = row_to_movie(&row(&HEADERS), &title_row("", "", "TV Movie")).ok().unwrap();
+        assert_eq!(movie.rating, None);
+        assert!(movie.watch_status == WatchStatus::WantToWatch);
+        assert!(movie.watch_history.is_empty());
+    }
+
+    #[test]
+    fn series_and_episodes_are_refused() {
+        let errors = row_to_movie(&row(&HEADERS), &title_row("9", "2020-05-04", "TV Series")).err();
+        assert_eq!(errors, Some(vec!["Not a movie (TV Series).".to_owned()]));
+        assert!(row_to_movie(&row(&HEADERS), &title_row("9", "2020-05-04", "TV Episode")).is_err());
+    }
+
+    #[test]
+    fn invalid_cells_are_reported() {
+        let errors = row_to_movie(&row(&HEADERS), &title_row("11", "04/05/2020", "Movie")).err();
+        assert_eq!(errors, Some(vec![
+            "IMDb rating has to be a whole number between 1 and 10.".to_owned(),
+            "Invalid date \"04/05/2020\".".to_owned(),
+        ]));
+    }
+}
@@ -0,0 +1,151 @@
+use seed::prelude::*;
+
+use super::{cell, has_column, parse_date};
+use super::super::{parse_rating, parse_title, parse_year, Movie, WatchEvent, WatchStatus};
+
+/// Letterboxd exports (`watched.csv`, `ratings.csv`, `diary.csv`, `watchlist.csv`) identify films by their URI.
+pub fn matches(headers: &[String]) -> bool {
+    has_column(headers, "Letterboxd URI")
+}
+
+/// `watchlist.csv` has the same columns as `watched.csv`, only the file name tells them apart.
+pub fn is_watchlist(file_name: &str) -> bool {
+    file_name.to_lowercase().contains("watchlist")
+}
+
+/// A row of `watched.csv`, `ratings.csv` or `diary.csv`.
+pub fn row_to_movie(headers: &[String], row: &[String]) -> Result<Movie, Vec<String>> {
+    let title = parse_title(cell(headers, row, "Name").unwrap_or_default());
+    let year = parse_year(cell(headers, row, "Year").unwrap_or_default());
+    let rating = parse_rating(cell(headers, row, "Rating").unwrap_or_default());
+    // `Date` is the watch date in `watched.csv`, but the rating date in `ratings.csv`.
+    let watched_on = match cell(headers, row, "Watched Date") {
+        Some(watched_date) => Some(watched_date),
+        None if not(has_column(headers, "Rating")) => cell(headers, row, "Date"),
+        None => None,
+    }
+    .map(parse_date)
+    .transpose();
+
+    match (title, year, rating, watched_on) {
+        (Ok(title), Ok(year), Ok(rating), Ok(watched_on)) => Ok(Movie {
+            title,
+            year,
+            rating,
+            // Everything in these exports has been watched.
+            watch_status: WatchStatus::Watched,
+            watch_history: watched_on.map(|watched_on| WatchEvent { watched_on }).into_iter().collect(),
+            ..Movie::default()
+        }),
+        (title, year, rating, watched_on) => Err(
+            vec![title.err(), year.err(), rating.err(), watched_on.err()].into_iter().flatten().collect()
+        ),
+    }
+}
+
+/// Films in the watchlist haven't been watched yet; their `Date` is when they were added to the watchlist.
+pub fn watchlist_row_to_movie(headers: &[String], row: &[String]) -> Result<Movie, Vec<String>> {
+    let title = parse_title(cell(headers, row, "Name").unwrap_or_default());
+    let year = parse_year(cell(headers, row, "Year").unwrap_or_default());
+
+    match (title, year) {
+        (Ok(title), Ok(year)) => Ok(Movie { title, year, watch_status: WatchStatus::WantToWatch, ..Movie::default() }),
+        (title, year) => Err(vec![title.err(), year.err()].into_iter().flatten().collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| (*cell).to_owned()).collect()
+    }
+
+    fn watched_on(year: i32, month: u32, day: u32) -> Vec<WatchEvent> {
+        vec![WatchEvent { watched_on: NaiveDate::from_ymd(year, month, day) }]
+    }
+
+    #[test]
+    fn exports_are_detected_by_the_uri_column() {
+        assert!(matches(&row(&["Date", "Name", "Year", "Letterboxd URI"])));
+        assert!(not(matches(&row(&["Date", "Name", "Year"]))));
+    }
+
+    #[test]
+    fn watchlist_is_detected_by_the_file_name() {
+        assert!(is_watchlist("watchlist.csv"));
+        assert!(is_watchlist("Letterboxd-Watchlist (2).csv"));
+        assert!(not(is_watchlist("watched.csv")));
+        assert!(not(is_watchlist("diary.csv")));
+    }
+
+    #[test]
+    fn diary_row_is_watched_on_the_watched_date() {
+        let headers = row(&["Date", "Name", "Year", "Letterboxd URI", "Rating", "Rewatch", "Tags", "Watched Date"]);
+        let movie = row_to_movie(
+            &headers,
+            &row(&["2020-06-02", "Parasite", "2019", "https://boxd.it/1", "4.5", "", "", "2020-06-01"]),
+        );
+        let expected = Movie {
+            title: "Parasite".to_owned(),
+            year: Some(2019),
+            rating: Some(9),
+            watch_status: WatchStatus::Watched,
+            watch_history: watched_on(2020, 6, 1),
+            ..Movie::default()
+        };
+        assert!(movie.ok() == Some(expected));
+    }
+
+    #[test]
+    fn watched_row_is_watched_on_the_date() {
+        let headers = row(&["Date", "Name", "Year", "Letterboxd URI"]);
+        let movie = row_to_movie(&headers, &row(&["2020-06-01", "Parasite", "2019", "https://boxd.it/1"])).ok().unwrap();
+        assert!(movie.watch_status == WatchStatus::Watched);
+        assert!(movie.watch_history == watched_on(2020, 6, 1));
+    }
+
+    #[test]
+    fn ratings_row_has_no_watch_date() {
+        // `Date` is the rating date.
+        let headers = row(&["Date", "Name", "Year", "Letterboxd URI", "Rating"]);
+        let movie = row_to_movie(&headers, &row(&["2020-06-01", "Parasite", "2019", "https://boxd.it/1", "5"]))
+            .ok()
+            .unwrap();
+        assert_eq!(movie.rating, Some(10));
+        assert!(movie.watch_status == WatchStatus::Watched);
+        assert!(movie.watch_history.is_empty());
+    }
+
+    #[test]
+    fn watchlist_row_is_want_to_watch() {
+        let headers = row(&["Date", "Name", "Year", "Letterboxd URI"]);
+        let movie = watchlist_row_to_movie(&headers, &row(&["2020-06-01", "Parasite", "2019", "https://boxd.it/1"]));
+        let expected = Movie {
+            title: "Parasite".to_owned(),
+            year: Some(2019),
+            watch_status: WatchStatus::WantToWatch,
+            ..Movie::default()
+        };
+        assert!(movie.ok() == Some(expected));
+    }
+
+    #[test]
+    fn invalid_cells_are_reported() {
+        let headers = row(&["Date", "Name", "Year", "Letterboxd URI", "Rating", "Watched Date"]);
+        let errors = row_to_movie(&headers, &row(&["2020-06-01", "", "1999", "https://boxd.it/1", "7", "yesterday"]))
+            .err()
+            .unwrap();
+        assert_eq!(errors, vec![
+            "Title is required.".to_owned(),
+            "Rating has to be between 0.5 and 5 stars, in half-star steps.".to_owned(),
+            "Invalid date \"yesterday\".".to_owned(),
+        ]);
+        assert_eq!(
+            watchlist_row_to_movie(&headers, &row(&["2020-06-01", "", "1999"])).err(),
+            Some(vec!["Title is required.".to_owned()]),
+        );
+    }
+}
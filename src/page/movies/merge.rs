@@ -0,0 +1,223 @@
+use seed::{prelude::*, *};
+
+use seed_style::{pc, px, rem};
+use seed_style::*;
+
+use std::collections::BTreeMap;
+
+use super::{added_at, Movie, MovieField, MovieId, WatchStatus};
+
+// ------ ------
+//     Model
+// ------ ------
+
+/// Two entries of the same movie; one of them survives the merge, the other one is deleted.
+pub struct Model {
+    movies: [(MovieId, Movie); 2],
+    /// Index into `movies`.
+    surviving: usize,
+    /// Index into `movies` of the value to keep, for each field with different values.
+    choices: BTreeMap<MovieField, usize>,
+}
+
+impl Model {
+    /// The older entry survives by default, so links to it keep working.
+    pub fn new(a: (MovieId, Movie), b: (MovieId, Movie)) -> Self {
+        let movies = if a.0 <= b.0 { [a, b] } else { [b, a] };
+        Self { movies, surviving: 0, choices: BTreeMap::new() }
+    }
+
+    pub fn surviving_id(&self) -> MovieId {
+        self.movies[self.surviving].0
+    }
+
+    pub fn merged_id(&self) -> MovieId {
+        self.movies[1 - self.surviving].0
+    }
+
+    fn differing_fields(&self) -> impl Iterator<Item = MovieField> + '_ {
+        let [(_, a), (_, b)] = &self.movies;
+        MovieField::ALL
+            .iter()
+            .copied()
+            // Genres are always united.
+            .filter(move |field| *field != MovieField::Genres && field.text(a) != field.text(b))
+    }
+
+    /// Values of the surviving movie, except the fields picked from the other one.
+    /// Genres, tags and watch history are united; the movie is watched if any of the entries is.
+    pub fn merged_movie(&self) -> Movie {
+        let (_, other) = &self.movies[1 - self.surviving];
+        let mut movie = self.movies[self.surviving].1.clone();
+
+        for field in self.differing_fields() {
+            let index = self.choices.get(&field).copied().unwrap_or(self.surviving);
+            field.copy(&self.movies[index].1, &mut movie);
+        }
+        for genre in &other.genres {
+            if not(movie.genres.contains(genre)) {
+                movie.genres.push(genre.clone());
+            }
+        }
+        for tag in &other.tags {
+            if not(movie.tags.contains(tag)) {
+                movie.tags.push(tag.clone());
+            }
+        }
+        for event in &other.watch_history {
+            if not(movie.watch_history.iter().any(|known| known.watched_on == event.watched_on)) {
+                movie.watch_history.push(event.clone());
+            }
+        }
+        movie.watch_history.sort_by_key(|event| event.watched_on);
+        if other.watch_status == WatchStatus::Watched {
+            movie.watch_status = WatchStatus::Watched;
+        }
+        movie
+    }
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    SurvivingChanged(usize),
+    ValueChosen(MovieField, usize),
+
+    // ------ handled by the parent ------
+
+    Merge,
+    Close,
+}
+
+pub fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::SurvivingChanged(index) => model.surviving = index,
+        Msg::ValueChosen(field, index) => {
+            model.choices.insert(field, index);
+        },
+
+        Msg::Merge | Msg::Close => {},
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+pub fn view(model: &Model) -> Node<Msg> {
+    let s_cell = || {
+        s()
+            .border("1px solid #dbdbdb")
+            .px(rem(0.5))
+            .py(rem(0.25))
+            .vertical_align(CssVerticalAlign::Top)
+    };
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .bg_color(rgba(10, 10, 10, 0.86))
+            .bottom("0")
+            .display(CssDisplay::Flex)
+            .justify_content(CssJustifyContent::Center)
+            .left("0")
+            .position(CssPosition::Fixed)
+            .right("0")
+            .top("0")
+            .z_index("40"),
+        div![
+            s()
+                .bg_color("white")
+                .border_radius(px(6))
+                .max_h("90vh")
+                .overflow_y("auto")
+                .p(rem(1.5))
+                .w(pc(90))
+                .max_w(px(900)),
+            h2![
+                s()
+                    .font_size(rem(1.5))
+                    .font_weight("600")
+                    .mb(rem(1)),
+                "Merge movies",
+            ],
+            p![
+                s()
+                    .color("#7a7a7a")
+                    .mb(rem(1)),
+                "Pick the value to keep for each field. Genres, tags, watch history and list memberships are combined. \
+                The other entry is deleted. Merging can't be undone.",
+            ],
+            table![
+                s()
+                    .raw("border-collapse: collapse;")
+                    .w(pc(100)),
+                thead![
+                    tr![
+                        th![s_cell()],
+                        model.movies.iter().enumerate().map(|(index, (movie_id, _))| th![
+                            s_cell(),
+                            label![
+                                input![
+                                    attrs!{
+                                        At::Type => "radio",
+                                        At::Name => "surviving",
+                                        At::Checked => (index == model.surviving).as_at_value(),
+                                    },
+                                    ev(Ev::Change, move |_| Msg::SurvivingChanged(index)),
+                                ],
+                                format!(" Keep entry added {}", added_at(*movie_id).format("%Y-%m-%d")),
+                            ]
+                        ]),
+                    ]
+                ],
+                tbody![
+                    model.differing_fields().map(|field| {
+                        let chosen = model.choices.get(&field).copied().unwrap_or(model.surviving);
+                        tr![
+                            th![s_cell(), field.label()],
+                            model.movies.iter().enumerate().map(|(index, (_, movie))| td![
+                                s_cell(),
+                                label![
+                                    input![
+                                        attrs!{
+                                            At::Type => "radio",
+                                            At::Name => field.label(),
+                                            At::Checked => (index == chosen).as_at_value(),
+                                        },
+                                        ev(Ev::Change, move |_| Msg::ValueChosen(field, index)),
+                                    ],
+                                    " ",
+                                    match field.text(movie) {
+                                        text if text.is_empty() => em!["empty"],
+                                        text => span![text],
+                                    },
+                                ]
+                            ]),
+                        ]
+                    })
+                ],
+            ],
+            div![
+                s()
+                    .mt(rem(1)),
+                button![
+                    C!["button"],
+                    s()
+                        .cursor(CssCursor::Pointer)
+                        .mr(rem(0.5)),
+                    "Merge",
+                    ev(Ev::Click, |_| Msg::Merge),
+                ],
+                button![
+                    C!["button"],
+                    s()
+                        .cursor(CssCursor::Pointer),
+                    "Cancel",
+                    ev(Ev::Click, |_| Msg::Close),
+                ],
+            ],
+        ]
+    ]
+}
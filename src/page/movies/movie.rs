@@ -0,0 +1,131 @@
+//! The movie data type and its formatting. It doesn't depend on Seed or the browser,
+//! so it can be used and tested on native targets.
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use super::rating::Review;
+use super::watch_status::{WatchEvent, WatchStatus};
+
+pub type MovieId = Ulid;
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Movie {
+    pub title: String,
+    pub year: Option<i32>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    /// Personal labels, e.g. "favorite" or "with kids".
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Actors.
+    #[serde(default)]
+    pub cast: Vec<String>,
+    /// Directors, writers, composers, etc.
+    #[serde(default)]
+    pub crew: Vec<String>,
+    /// Minutes.
+    pub runtime: Option<u32>,
+    /// Personal rating in half-stars, `1..=10`.
+    pub rating: Option<u8>,
+    /// Half-stars average of all users' ratings, computed by the server.
+    #[serde(default, skip_serializing)]
+    pub average_rating: Option<f64>,
+    #[serde(default)]
+    pub review: Option<Review>,
+    pub poster_url: Option<String>,
+    /// A small version of the uploaded poster for the movie list.
+    #[serde(default)]
+    pub thumbnail_url: Option<String>,
+    pub synopsis: Option<String>,
+    #[serde(default)]
+    pub watch_status: WatchStatus,
+    #[serde(default)]
+    pub watch_history: Vec<WatchEvent>,
+    /// Incremented by the server on every change.
+    /// Updates send it back, so the server can refuse changes based on an outdated movie.
+    #[serde(default)]
+    pub version: u64,
+}
+
+impl Movie {
+    /// Reaching `WatchStatus::Watched` records a new `WatchEvent`.
+    /// Returns `false` if the transition isn't allowed.
+    pub fn change_watch_status(&mut self, watch_status: WatchStatus, today: NaiveDate) -> bool {
+        if !self.watch_status.can_change_to(watch_status) {
+            return false;
+        }
+        if watch_status == WatchStatus::Watched {
+            self.watch_history.push(WatchEvent { watched_on: today });
+        }
+        self.watch_status = watch_status;
+        true
+    }
+
+    pub fn last_watched_on(&self) -> Option<NaiveDate> {
+        self.watch_history.iter().map(|event| event.watched_on).max()
+    }
+
+    /// Fills in values missing in `self` from `other` and adds `other`'s genres, tags, cast, crew and watch events.
+    /// Values already in `self` win. Returns `false` if nothing has changed.
+    pub fn merge(&mut self, other: Movie) -> bool {
+        fn fill<T>(target: &mut Option<T>, value: Option<T>) -> bool {
+            if target.is_none() && value.is_some() {
+                *target = value;
+                return true;
+            }
+            false
+        }
+        fn unite(target: &mut Vec<String>, values: Vec<String>) -> bool {
+            let mut changed = false;
+            for value in values {
+                if !target.contains(&value) {
+                    target.push(value);
+                    changed = true;
+                }
+            }
+            changed
+        }
+        let mut changed = fill(&mut self.year, other.year);
+        changed |= fill(&mut self.runtime, other.runtime);
+        changed |= fill(&mut self.rating, other.rating);
+        changed |= fill(&mut self.review, other.review);
+        if fill(&mut self.poster_url, other.poster_url) {
+            self.thumbnail_url = other.thumbnail_url;
+            changed = true;
+        }
+        changed |= fill(&mut self.synopsis, other.synopsis);
+
+        changed |= unite(&mut self.genres, other.genres);
+        changed |= unite(&mut self.tags, other.tags);
+        changed |= unite(&mut self.cast, other.cast);
+        changed |= unite(&mut self.crew, other.crew);
+        for event in other.watch_history {
+            if !self.watch_history.iter().any(|known| known.watched_on == event.watched_on) {
+                self.watch_history.push(event);
+                changed = true;
+            }
+        }
+        self.watch_history.sort_by_key(|event| event.watched_on);
+
+        if other.watch_status == WatchStatus::Watched && self.watch_status != WatchStatus::Watched {
+            self.watch_status = WatchStatus::Watched;
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// `MovieId` is a ULID, so it already contains the time when the movie has been added.
+pub fn added_at(movie_id: MovieId) -> DateTime<Local> {
+    movie_id.datetime().with_timezone(&Local)
+}
+
+pub fn format_runtime(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}min", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}min", hours, minutes),
+    }
+}
@@ -0,0 +1,437 @@
+use seed::{prelude::*, *};
+
+use seed_style::{pc, px, rem};
+use seed_style::*;
+
+use chrono::prelude::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{added_at, format_runtime, format_stars, Movie, MovieId, WatchStatus};
+use super::rating::MAX_HALF_STARS;
+
+/// A movie waiting for a month is twice as likely to be picked as a movie added today,
+/// a movie waiting for a year is 13 times as likely.
+const NEGLECT_DAYS_PER_WEIGHT: f64 = 30.;
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(seed: u64) -> Model {
+    Model {
+        constraints: Constraints::default(),
+        max_runtime: String::new(),
+        seed,
+        rejected: Vec::new(),
+    }
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    constraints: Constraints,
+    /// The text of the max runtime input; `constraints.max_runtime` holds the parsed value.
+    max_runtime: String,
+    seed: u64,
+    /// Movies skipped by re-rolls; they aren't picked again until nothing else matches.
+    rejected: Vec<MovieId>,
+}
+
+#[derive(Default)]
+pub struct Constraints {
+    /// Minutes; movies without runtime don't match.
+    pub max_runtime: Option<u32>,
+    /// The movie has to have at least one of them; no genres match all movies.
+    pub genres: BTreeSet<String>,
+    /// Half-stars; the personal rating is used if there is one, the average rating otherwise.
+    pub min_rating: Option<u8>,
+}
+
+impl Constraints {
+    pub fn matches(&self, movie: &Movie) -> bool {
+        let runtime_matches = match (self.max_runtime, movie.runtime) {
+            (None, _) => true,
+            (Some(max_runtime), Some(runtime)) => runtime <= max_runtime,
+            (Some(_), None) => false,
+        };
+        let genres_matches =
+            self.genres.is_empty() || movie.genres.iter().any(|genre| self.genres.contains(genre));
+        let rating_matches = match (self.min_rating, movie.rating.map(f64::from).or(movie.average_rating)) {
+            (None, _) => true,
+            (Some(min_rating), Some(rating)) => rating >= f64::from(min_rating),
+            (Some(_), None) => false,
+        };
+        runtime_matches && genres_matches && rating_matches
+    }
+}
+
+/// SplitMix64 - tiny and good enough for picking movies; the same seed always gives the same numbers.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// `0.0..1.0`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+/// The seed of the next re-roll.
+pub fn next_seed(seed: u64) -> u64 {
+    Rng(seed).next_u64()
+}
+
+/// Movies the user wants to watch that match the constraints.
+pub fn candidates<'a>(
+    movies: &'a BTreeMap<MovieId, Movie>,
+    constraints: &'a Constraints,
+) -> impl Iterator<Item = (MovieId, &'a Movie)> + 'a {
+    movies
+        .iter()
+        .filter(move |(_, movie)| movie.watch_status == WatchStatus::WantToWatch && constraints.matches(movie))
+        .map(|(movie_id, movie)| (*movie_id, movie))
+}
+
+/// Grows with the days since the movie has been added.
+pub fn neglect_weight(movie_id: MovieId, today: NaiveDate) -> f64 {
+    let days = (today - added_at(movie_id).naive_local().date()).num_days().max(0);
+    1. + days as f64 / NEGLECT_DAYS_PER_WEIGHT
+}
+
+/// A weighted random candidate; long-neglected movies are more likely.
+/// `rejected` movies are picked only if there are no other candidates.
+/// Returns `None` if no movie matches the constraints.
+pub fn pick(
+    movies: &BTreeMap<MovieId, Movie>,
+    constraints: &Constraints,
+    rejected: &[MovieId],
+    today: NaiveDate,
+    seed: u64,
+) -> Option<MovieId> {
+    let mut movie_ids = candidates(movies, constraints)
+        .filter(|(movie_id, _)| not(rejected.contains(movie_id)))
+        .map(|(movie_id, _)| movie_id)
+        .collect::<Vec<_>>();
+    if movie_ids.is_empty() {
+        movie_ids = candidates(movies, constraints).map(|(movie_id, _)| movie_id).collect();
+    }
+    let weights = movie_ids
+        .iter()
+        .map(|movie_id| neglect_weight(*movie_id, today))
+        .collect::<Vec<_>>();
+
+    let mut remaining = Rng(seed).next_f64() * weights.iter().sum::<f64>();
+    for (movie_id, weight) in movie_ids.iter().zip(&weights) {
+        if remaining < *weight {
+            return Some(*movie_id);
+        }
+        remaining -= weight;
+    }
+    // Rounding errors may leave a tiny remainder.
+    movie_ids.last().copied()
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    MaxRuntimeChanged(String),
+    GenreToggled(String),
+    MinRatingChanged(String),
+    /// Contains the currently picked movie.
+    Reroll(Option<MovieId>),
+
+    // ------ handled by the parent ------
+
+    Close,
+}
+
+pub fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::MaxRuntimeChanged(max_runtime) => {
+            model.constraints.max_runtime = max_runtime.trim().parse().ok();
+            model.max_runtime = max_runtime;
+        },
+        Msg::GenreToggled(genre) => {
+            if not(model.constraints.genres.remove(&genre)) {
+                model.constraints.genres.insert(genre);
+            }
+        },
+        Msg::MinRatingChanged(min_rating) => model.constraints.min_rating = min_rating.parse().ok(),
+        Msg::Reroll(picked) => {
+            model.rejected.extend(picked);
+            model.seed = next_seed(model.seed);
+        },
+
+        Msg::Close => {},
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+pub fn view(model: &Model, movies: &BTreeMap<MovieId, Movie>, base_url: &Url) -> Node<Msg> {
+    let candidate_count = candidates(movies, &model.constraints).count();
+    let picked = pick(movies, &model.constraints, &model.rejected, Local::today().naive_local(), model.seed);
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .bg_color(rgba(10, 10, 10, 0.86))
+            .bottom("0")
+            .display(CssDisplay::Flex)
+            .justify_content(CssJustifyContent::Center)
+            .left("0")
+            .position(CssPosition::Fixed)
+            .right("0")
+            .top("0")
+            .z_index("40"),
+        div![
+            s()
+                .bg_color("white")
+                .border_radius(px(6))
+                .max_h("90vh")
+                .overflow_y("auto")
+                .p(rem(1.5))
+                .w(pc(90))
+                .max_w(px(600)),
+            h2![
+                s()
+                    .font_size(rem(1.5))
+                    .font_weight("600")
+                    .mb(rem(1)),
+                "Pick something for tonight",
+            ],
+            view_constraints(model, movies),
+            p![
+                s()
+                    .color("#7a7a7a")
+                    .font_size(rem(0.875))
+                    .my(rem(1)),
+                format!("{} matching movies you want to watch. Movies waiting longer are more likely.", candidate_count),
+            ],
+            match picked.and_then(|movie_id| movies.get(&movie_id).map(|movie| (movie_id, movie))) {
+                Some((movie_id, movie)) => view_picked_movie(movie_id, movie, base_url),
+                None => p![
+                    s()
+                        .py(rem(1))
+                        .text_align(CssTextAlign::Center),
+                    "No movie matches. Try looser constraints.",
+                ],
+            },
+            div![
+                s()
+                    .mt(rem(1)),
+                button![
+                    C!["button"],
+                    s()
+                        .cursor(CssCursor::Pointer)
+                        .mr(rem(0.5)),
+                    attrs!{At::Disabled => (candidate_count < 2).as_at_value()},
+                    "Re-roll",
+                    ev(Ev::Click, move |_| Msg::Reroll(picked)),
+                ],
+                button![
+                    C!["button"],
+                    s()
+                        .cursor(CssCursor::Pointer),
+                    "Close",
+                    ev(Ev::Click, |_| Msg::Close),
+                ],
+            ],
+        ]
+    ]
+}
+
+fn view_constraints(model: &Model, movies: &BTreeMap<MovieId, Movie>) -> Node<Msg> {
+    let genres = movies
+        .values()
+        .filter(|movie| movie.watch_status == WatchStatus::WantToWatch)
+        .flat_map(|movie| &movie.genres)
+        .collect::<BTreeSet<_>>();
+    div![
+        div![
+            s()
+                .display(CssDisplay::Flex)
+                .mb(rem(0.75)),
+            label![
+                s()
+                    .mr(rem(1)),
+                "Max runtime ",
+                input![
+                    C!["input"],
+                    s()
+                        .w(px(80)),
+                    attrs!{
+                        At::Type => "number",
+                        At::Min => 1,
+                        At::Placeholder => "minutes",
+                        At::Value => model.max_runtime,
+                    },
+                    input_ev(Ev::Input, Msg::MaxRuntimeChanged),
+                ],
+            ],
+            label![
+                "Min rating ",
+                select![
+                    input_ev(Ev::Change, Msg::MinRatingChanged),
+                    option![
+                        attrs!{
+                            At::Value => "",
+                            At::Selected => model.constraints.min_rating.is_none().as_at_value(),
+                        },
+                        "Any",
+                    ],
+                    (1..=MAX_HALF_STARS).map(|half_stars| option![
+                        attrs!{
+                            At::Value => half_stars,
+                            At::Selected => (model.constraints.min_rating == Some(half_stars)).as_at_value(),
+                        },
+                        format!("★ {}+", format_stars(half_stars)),
+                    ]),
+                ],
+            ],
+        ],
+        IF!(not(genres.is_empty()) => div![
+            genres.into_iter().map(|genre| {
+                let toggled_genre = genre.clone();
+                label![
+                    s()
+                        .display(CssDisplay::InlineBlock)
+                        .mr(rem(0.75)),
+                    input![
+                        attrs!{
+                            At::Type => "checkbox",
+                            At::Checked => model.constraints.genres.contains(genre).as_at_value(),
+                        },
+                        ev(Ev::Change, move |_| Msg::GenreToggled(toggled_genre.clone())),
+                    ],
+                    format!(" {}", genre),
+                ]
+            })
+        ]),
+    ]
+}
+
+fn view_picked_movie(movie_id: MovieId, movie: &Movie, base_url: &Url) -> Node<Msg> {
+    let details = vec![
+        movie.year.map(|year| year.to_string()),
+        movie.runtime.map(format_runtime),
+        movie.rating.map(f64::from).or(movie.average_rating).map(|rating| format!("★ {:.1}", rating / 2.)),
+        Some(format!("added {}", added_at(movie_id).format("%Y-%m-%d"))),
+    ];
+    div![
+        s()
+            .border("1px solid #ededed")
+            .border_radius(px(4))
+            .p(rem(1))
+            .text_align(CssTextAlign::Center),
+        a![
+            s()
+                .font_size(rem(1.5))
+                .font_weight("600"),
+            attrs!{At::Href => crate::Urls::new(base_url).movie(movie_id)},
+            &movie.title,
+        ],
+        p![
+            s()
+                .color("#7a7a7a"),
+            details.into_iter().flatten().collect::<Vec<_>>().join(" · "),
+        ],
+        IF!(not(movie.genres.is_empty()) => p![movie.genres.join(", ")]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures::default_movies;
+
+    /// A movie id created at the start of the given day.
+    fn added_on(date: NaiveDate) -> MovieId {
+        let timestamp_ms = date.and_hms(0, 0, 0).timestamp_millis() as u128;
+        // The timestamp is in the most significant 48 bits.
+        MovieId::from(timestamp_ms << 80)
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd(2020, 6, 1)
+    }
+
+    #[test]
+    fn same_seed_gives_same_pick() {
+        let movies = default_movies(&[
+            added_on(NaiveDate::from_ymd(2020, 1, 1)),
+            added_on(NaiveDate::from_ymd(2020, 3, 1)),
+            added_on(NaiveDate::from_ymd(2020, 5, 1)),
+        ]);
+        let constraints = Constraints::default();
+        for seed in 0..100 {
+            let picked = pick(&movies, &constraints, &[], today(), seed);
+            assert!(picked.is_some());
+            assert_eq!(picked, pick(&movies, &constraints, &[], today(), seed));
+        }
+    }
+
+    #[test]
+    fn rejected_movies_are_picked_only_without_other_candidates() {
+        let (a, b) = (added_on(NaiveDate::from_ymd(2020, 1, 1)), added_on(NaiveDate::from_ymd(2020, 2, 1)));
+        let movies = default_movies(&[a, b]);
+        let constraints = Constraints::default();
+        for seed in 0..100 {
+            assert_eq!(pick(&movies, &constraints, &[a], today(), seed), Some(b));
+            assert!(pick(&movies, &constraints, &[a, b], today(), seed).is_some());
+        }
+        assert_eq!(pick(&BTreeMap::new(), &constraints, &[a], today(), 0), None);
+    }
+
+    #[test]
+    fn constraints_match_movies_with_missing_values_only_without_limits() {
+        let movie = Movie { genres: vec!["Drama".to_owned()], ..Movie::default() };
+        assert!(Constraints::default().matches(&movie));
+        assert!(Constraints::default().matches(&Movie::default()));
+
+        let max_runtime = Constraints { max_runtime: Some(120), ..Constraints::default() };
+        assert!(!max_runtime.matches(&movie));
+        assert!(max_runtime.matches(&Movie { runtime: Some(120), ..Movie::default() }));
+        assert!(!max_runtime.matches(&Movie { runtime: Some(121), ..Movie::default() }));
+
+        let min_rating = Constraints { min_rating: Some(6), ..Constraints::default() };
+        assert!(!min_rating.matches(&movie));
+        assert!(min_rating.matches(&Movie { average_rating: Some(6.5), ..Movie::default() }));
+        // The personal rating wins over the average one.
+        assert!(!min_rating.matches(&Movie { rating: Some(5), average_rating: Some(9.), ..Movie::default() }));
+
+        let genres = Constraints {
+            genres: vec!["Comedy".to_owned(), "Drama".to_owned()].into_iter().collect(),
+            ..Constraints::default()
+        };
+        assert!(genres.matches(&movie));
+        assert!(!genres.matches(&Movie::default()));
+    }
+
+    #[test]
+    fn neglected_movies_are_picked_more_often() {
+        let old_movie = added_on(NaiveDate::from_ymd(2019, 6, 1));
+        let new_movie = added_on(today());
+        let movies = default_movies(&[old_movie, new_movie]);
+        let constraints = Constraints::default();
+        let old_picks = (0..1000)
+            .filter(|seed| pick(&movies, &constraints, &[], today(), *seed) == Some(old_movie))
+            .count();
+        // The old movie's weight is about 13 times higher.
+        assert!(old_picks > 850, "old movie picked {} times", old_picks);
+    }
+}
+
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+/// Ratings are stored in half-stars: `1..=10` represents 0.5 - 5 stars.
+pub const MAX_HALF_STARS: u8 = 10;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Review {
+    pub text: String,
+    #[serde(default)]
+    pub spoiler: bool,
+}
+
+/// E.g. `7` => `"3.5"`, `8` => `"4"`.
+pub fn format_stars(half_stars: u8) -> String {
+    if half_stars % 2 == 0 {
+        (half_stars / 2).to_string()
+    } else {
+        format!("{}.5", half_stars / 2)
+    }
+}
+
+/// Accepts whole and half stars - e.g. `"3"` or `"3.5"`.
+pub fn parse_stars(text: &str) -> Option<u8> {
+    let stars = text.trim().parse::<f64>().ok()?;
+    let half_stars = stars * 2.;
+    if half_stars.fract() != 0. || half_stars < 1. || half_stars > f64::from(MAX_HALF_STARS) {
+        return None;
+    }
+    Some(half_stars as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_stars_shows_halves_only_when_needed() {
+        assert_eq!(format_stars(1), "0.5");
+        assert_eq!(format_stars(7), "3.5");
+        assert_eq!(format_stars(8), "4");
+        assert_eq!(format_stars(MAX_HALF_STARS), "5");
+    }
+
+    #[test]
+    fn parse_stars_accepts_whole_and_half_stars() {
+        assert_eq!(parse_stars("0.5"), Some(1));
+        assert_eq!(parse_stars(" 3.5 "), Some(7));
+        assert_eq!(parse_stars("4"), Some(8));
+        assert_eq!(parse_stars("5.0"), Some(MAX_HALF_STARS));
+    }
+
+    #[test]
+    fn parse_stars_rejects_out_of_range_and_invalid_input() {
+        assert_eq!(parse_stars("0"), None);
+        assert_eq!(parse_stars("5.5"), None);
+        assert_eq!(parse_stars("-1"), None);
+        assert_eq!(parse_stars("3.25"), None);
+        assert_eq!(parse_stars("inf"), None);
+        assert_eq!(parse_stars("NaN"), None);
+        assert_eq!(parse_stars("three"), None);
+        assert_eq!(parse_stars(""), None);
+    }
+
+    #[test]
+    fn formatted_stars_are_parsed_back() {
+        for half_stars in 1..=MAX_HALF_STARS {
+            assert_eq!(parse_stars(&format_stars(half_stars)), Some(half_stars));
+        }
+    }
+}
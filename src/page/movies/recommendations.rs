@@ -0,0 +1,241 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{Movie, MovieId, WatchStatus};
+use super::rating::MAX_HALF_STARS;
+
+const GENRES_WEIGHT: f64 = 1.;
+const TAGS_WEIGHT: f64 = 0.5;
+const CAST_WEIGHT: f64 = 1.;
+const CREW_WEIGHT: f64 = 1.;
+/// Sharing more people doesn't make movies any more similar.
+const MAX_SHARED_PEOPLE: usize = 3;
+
+/// A movie the user might enjoy, explained by the rated movie contributing the most to its score.
+pub struct Recommendation {
+    pub movie_id: MovieId,
+    pub score: f64,
+    pub because_of: MovieId,
+}
+
+/// Lowercase genres, tags, cast and crew of a movie, prepared for repeated comparisons.
+struct Features {
+    genres: BTreeSet<String>,
+    tags: BTreeSet<String>,
+    cast: BTreeSet<String>,
+    crew: BTreeSet<String>,
+}
+
+impl Features {
+    fn new(movie: &Movie) -> Self {
+        fn lowercase_set(values: &[String]) -> BTreeSet<String> {
+            values.iter().map(|value| value.to_lowercase()).collect()
+        }
+        Self {
+            genres: lowercase_set(&movie.genres),
+            tags: lowercase_set(&movie.tags),
+            cast: lowercase_set(&movie.cast),
+            crew: lowercase_set(&movie.crew),
+        }
+    }
+
+    fn similarity(&self, other: &Features) -> f64 {
+        GENRES_WEIGHT * jaccard_index(&self.genres, &other.genres)
+            + TAGS_WEIGHT * jaccard_index(&self.tags, &other.tags)
+            + CAST_WEIGHT * shared_people(&self.cast, &other.cast)
+            + CREW_WEIGHT * shared_people(&self.crew, &other.crew)
+    }
+}
+
+/// `0.0` for unrelated movies; grows with shared genres, tags, cast and crew.
+/// Values are compared case-insensitively.
+pub fn similarity(a: &Movie, b: &Movie) -> f64 {
+    Features::new(a).similarity(&Features::new(b))
+}
+
+/// The share of common values, `0.0..=1.0`.
+fn jaccard_index(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// `0.0..=1.0`
+fn shared_people(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let shared = a.intersection(b).count();
+    shared.min(MAX_SHARED_PEOPLE) as f64 / MAX_SHARED_PEOPLE as f64
+}
+
+/// Other movies similar to the given one, the most similar first; unrelated movies are skipped.
+/// Ties are sorted by `MovieId`, i.e. the oldest entry first.
+pub fn more_like_this(
+    movie_id: MovieId,
+    movie: &Movie,
+    movies: &BTreeMap<MovieId, Movie>,
+    limit: usize,
+) -> Vec<(MovieId, f64)> {
+    let features = Features::new(movie);
+    let mut similar_movies = movies
+        .iter()
+        .filter(|(other_id, _)| **other_id != movie_id)
+        .map(|(other_id, other)| (*other_id, features.similarity(&Features::new(other))))
+        .filter(|(_, score)| *score > 0.)
+        .collect::<Vec<_>>();
+    sort_by_score(&mut similar_movies, |(movie_id, score)| (*movie_id, *score));
+    similar_movies.truncate(limit);
+    similar_movies
+}
+
+/// Movies the user wants to watch, scored by their similarity to rated movies.
+/// Movies rated above the user's average rating pull similar movies up, the ones below push them down.
+/// When all ratings are equal, e.g. there is only one, the middle of the rating scale is used instead of the average,
+/// so a good rating still recommends something.
+/// Only movies with a positive score are recommended; nothing is recommended without ratings.
+pub fn you_might_enjoy(movies: &BTreeMap<MovieId, Movie>, limit: usize) -> Vec<Recommendation> {
+    let rated_movies = movies
+        .iter()
+        .filter_map(|(movie_id, movie)| {
+            movie.rating.map(|rating| (*movie_id, Features::new(movie), f64::from(rating)))
+        })
+        .collect::<Vec<_>>();
+    let first_rating = match rated_movies.first() {
+        Some((_, _, rating)) => *rating,
+        None => return Vec::new(),
+    };
+    let neutral_rating = if rated_movies.iter().all(|(_, _, rating)| *rating == first_rating) {
+        f64::from(1 + MAX_HALF_STARS) / 2.
+    } else {
+        rated_movies.iter().map(|(_, _, rating)| rating).sum::<f64>() / rated_movies.len() as f64
+    };
+
+    let mut recommendations = movies
+        .iter()
+        .filter(|(_, movie)| movie.watch_status == WatchStatus::WantToWatch)
+        .filter_map(|(movie_id, movie)| {
+            let features = Features::new(movie);
+            let contributions = rated_movies
+                .iter()
+                .filter(|(rated_id, _, _)| rated_id != movie_id)
+                .map(|(rated_id, rated, rating)| {
+                    let preference = (rating - neutral_rating) / f64::from(MAX_HALF_STARS);
+                    (*rated_id, features.similarity(rated) * preference)
+                })
+                .collect::<Vec<_>>();
+            let score = contributions.iter().map(|(_, contribution)| contribution).sum::<f64>();
+            // `max_by` returns the last maximum; `rev` keeps the oldest movie among equal ones.
+            let (because_of, top_contribution) = contributions
+                .into_iter()
+                .rev()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))?;
+            if score > 0. && top_contribution > 0. {
+                Some(Recommendation { movie_id: *movie_id, score, because_of })
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    sort_by_score(&mut recommendations, |recommendation| (recommendation.movie_id, recommendation.score));
+    recommendations.truncate(limit);
+    recommendations
+}
+
+/// The highest score first; ties by `MovieId`, so the order is stable.
+fn sort_by_score<T>(items: &mut [T], key: impl Fn(&T) -> (MovieId, f64)) {
+    items.sort_by(|a, b| {
+        let ((a_id, a_score), (b_id, b_score)) = (key(a), key(b));
+        b_score.partial_cmp(&a_score).unwrap_or(Ordering::Equal).then(a_id.cmp(&b_id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures::collection;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| (*value).to_owned()).collect()
+    }
+
+    fn rated(genres: &[&str], rating: u8) -> Movie {
+        Movie {
+            genres: strings(genres),
+            rating: Some(rating),
+            watch_status: WatchStatus::Watched,
+            ..Movie::default()
+        }
+    }
+
+    fn wanted(genres: &[&str]) -> Movie {
+        Movie { genres: strings(genres), ..Movie::default() }
+    }
+
+    #[test]
+    fn similarity_ignores_case() {
+        let a = Movie { genres: strings(&["Drama"]), cast: strings(&["Toshiro Mifune"]), ..Movie::default() };
+        let b = Movie { genres: strings(&["drama"]), cast: strings(&["TOSHIRO MIFUNE"]), ..Movie::default() };
+        assert_eq!(similarity(&a, &b), GENRES_WEIGHT + CAST_WEIGHT / MAX_SHARED_PEOPLE as f64);
+        assert_eq!(similarity(&a, &Movie::default()), 0.);
+    }
+
+    #[test]
+    fn similarity_caps_shared_people() {
+        let people = ["A", "B", "C", "D", "E"];
+        let a = Movie { crew: strings(&people), ..Movie::default() };
+        let b = Movie { crew: strings(&people[..MAX_SHARED_PEOPLE]), ..Movie::default() };
+        assert_eq!(similarity(&a, &a), CREW_WEIGHT);
+        assert_eq!(similarity(&a, &b), CREW_WEIGHT);
+    }
+
+    #[test]
+    fn more_like_this_sorts_ties_by_id_and_skips_unrelated_movies() {
+        let (movie_ids, movies) = collection(vec![
+            wanted(&["Drama"]),
+            wanted(&["Comedy"]),
+            wanted(&["Drama", "War"]),
+            wanted(&["Drama", "War"]),
+            wanted(&["Drama"]),
+        ]);
+        let movie = &movies[&movie_ids[2]];
+        let similar_ids = more_like_this(movie_ids[2], movie, &movies, 10)
+            .into_iter()
+            .map(|(movie_id, _)| movie_id)
+            .collect::<Vec<_>>();
+        assert_eq!(similar_ids, vec![movie_ids[3], movie_ids[0], movie_ids[4]]);
+        assert_eq!(more_like_this(movie_ids[2], movie, &movies, 2).len(), 2);
+    }
+
+    #[test]
+    fn you_might_enjoy_follows_ratings_above_and_below_average() {
+        let (movie_ids, movies) = collection(vec![
+            rated(&["Horror"], 2),
+            rated(&["Drama"], 10),
+            wanted(&["Horror"]),
+            wanted(&["Drama"]),
+            wanted(&["Drama", "Romance"]),
+            rated(&["Drama"], 8),
+        ]);
+        let recommendations = you_might_enjoy(&movies, 10);
+        let recommended_ids = recommendations.iter().map(|recommendation| recommendation.movie_id).collect::<Vec<_>>();
+        assert_eq!(recommended_ids, vec![movie_ids[3], movie_ids[4]]);
+        // Both rated dramas contribute equally; the older one explains the recommendation.
+        assert_eq!(recommendations[0].because_of, movie_ids[1]);
+        // Watched movies aren't recommended and nothing is recommended without ratings.
+        assert!(you_might_enjoy(&collection(vec![wanted(&["Drama"])]).1, 10).is_empty());
+    }
+
+    #[test]
+    fn equal_ratings_are_compared_to_the_middle_of_the_scale() {
+        let (movie_ids, movies) = collection(vec![rated(&["Drama"], 9), wanted(&["Drama"]), wanted(&["Comedy"])]);
+        let recommended_ids = you_might_enjoy(&movies, 10)
+            .into_iter()
+            .map(|recommendation| recommendation.movie_id)
+            .collect::<Vec<_>>();
+        assert_eq!(recommended_ids, vec![movie_ids[1]]);
+
+        let (_, movies) = collection(vec![rated(&["Drama"], 3), rated(&["Drama"], 3), wanted(&["Drama"])]);
+        assert!(you_might_enjoy(&movies, 10).is_empty());
+    }
+}
+
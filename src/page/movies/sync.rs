@@ -0,0 +1,278 @@
+use seed::{prelude::*, *};
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, VecDeque};
+
+use super::{delete_movie, patch_movie, post_merge, post_movie, post_movies, Movie, MovieId};
+use super::super::lists::{post_list_movies, ListId};
+
+const MOVIES_STORAGE_KEY: &str = "movies";
+const QUEUE_STORAGE_KEY: &str = "movies_sync_queue";
+/// The first retry of a replay failed by a network error; every next one waits twice as long.
+const MIN_RETRY_DELAY_MS: u32 = 1_000;
+const MAX_RETRY_DELAY_MS: u32 = 60_000;
+
+// ------ Request ------
+
+/// A change to send to the backend.
+/// It's serializable, so it can wait in LocalStorage until the browser is online again.
+#[derive(Serialize, Deserialize, Clone)]
+pub enum Request {
+    Create(MovieId, Movie),
+    CreateMany(Vec<(MovieId, Movie)>),
+    Update(MovieId, Movie),
+    Delete(MovieId),
+    Merge { surviving_id: MovieId, merged_id: MovieId, movie: Movie },
+    AddToList(ListId, Vec<MovieId>),
+}
+
+/// The movie changed by the request and its new version.
+pub type NewVersion = Option<(MovieId, u64)>;
+
+impl Request {
+    pub async fn send(self) -> fetch::Result<NewVersion> {
+        match self {
+            Self::Create(movie_id, movie) => post_movie(movie_id, movie).await.map(|_| None),
+            Self::CreateMany(movies) => post_movies(movies).await.map(|_| None),
+            Self::Update(movie_id, movie) => {
+                patch_movie(movie_id, movie).await.map(|version| Some((movie_id, version)))
+            },
+            Self::Delete(movie_id) => delete_movie(movie_id).await.map(|_| None),
+            Self::Merge { surviving_id, merged_id, movie } => {
+                post_merge(surviving_id, merged_id, movie).await.map(|version| Some((surviving_id, version)))
+            },
+            Self::AddToList(list_id, movie_ids) => post_list_movies(list_id, movie_ids).await.map(|_| None),
+        }
+    }
+
+    /// The movie whose version the request checks.
+    pub fn versioned_movie_id(&self) -> Option<MovieId> {
+        match self {
+            Self::Update(movie_id, _) | Self::Merge { surviving_id: movie_id, .. } => Some(*movie_id),
+            Self::Create(..) | Self::CreateMany(_) | Self::Delete(_) | Self::AddToList(..) => None,
+        }
+    }
+
+    /// Makes the same change in `movies` - e.g. in fresh movies from the server.
+    fn apply(&self, movies: &mut BTreeMap<MovieId, Movie>) {
+        match self {
+            Self::Create(movie_id, movie) | Self::Update(movie_id, movie) => {
+                movies.insert(*movie_id, movie.clone());
+            },
+            Self::CreateMany(new_movies) => movies.extend(new_movies.iter().cloned()),
+            Self::Delete(movie_id) => {
+                movies.remove(movie_id);
+            },
+            Self::Merge { surviving_id, merged_id, movie } => {
+                movies.insert(*surviving_id, movie.clone());
+                movies.remove(merged_id);
+            },
+            // Movies don't know about lists.
+            Self::AddToList(..) => {},
+        }
+    }
+}
+
+pub fn is_network_error(fetch_error: &FetchError) -> bool {
+    matches!(fetch_error, FetchError::NetworkError(_))
+}
+
+/// The server has refused the change, because the movie has a newer version.
+pub fn is_conflict(fetch_error: &FetchError) -> bool {
+    matches!(fetch_error, FetchError::StatusError(status) if status.code == 409 || status.code == 412)
+}
+
+// ------ SyncQueue ------
+
+/// Requests made while offline, persisted in LocalStorage and replayed in order, one by one.
+pub struct SyncQueue {
+    requests: VecDeque<Request>,
+    /// The browser is offline; failed requests alone don't mean it, the server may be just unreachable.
+    pub offline: bool,
+    /// The first request is being sent.
+    pub replaying: bool,
+    /// Replays failed by network errors in a row.
+    failed_replays: u32,
+}
+
+impl SyncQueue {
+    pub fn load() -> Self {
+        Self {
+            requests: LocalStorage::get(QUEUE_STORAGE_KEY).unwrap_or_default(),
+            offline: not(window().navigator().on_line()),
+            replaying: false,
+            failed_replays: 0,
+        }
+    }
+
+    /// Requests can't overtake the queued ones, otherwise the replay could e.g. update a deleted movie.
+    pub fn should_queue(&self) -> bool {
+        self.offline || not(self.requests.is_empty())
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    pub fn push(&mut self, request: Request) {
+        self.requests.push_back(request);
+        self.save();
+    }
+
+    /// Milliseconds to wait before the next replay; grows with every call until `reset_retry_delay`.
+    pub fn next_retry_delay(&mut self) -> u32 {
+        let delay = MIN_RETRY_DELAY_MS.saturating_mul(1 << self.failed_replays.min(16)).min(MAX_RETRY_DELAY_MS);
+        self.failed_replays = self.failed_replays.saturating_add(1);
+        delay
+    }
+
+    pub fn reset_retry_delay(&mut self) {
+        self.failed_replays = 0;
+    }
+
+    pub fn front(&self) -> Option<&Request> {
+        self.requests.front()
+    }
+
+    pub fn pop_front(&mut self) -> Option<Request> {
+        let request = self.requests.pop_front();
+        self.save();
+        request
+    }
+
+    /// Queued changes of the movie are based on the version the server has just replaced.
+    pub fn set_version(&mut self, movie_id: MovieId, version: u64) {
+        for request in &mut self.requests {
+            match request {
+                Request::Update(id, movie) | Request::Merge { surviving_id: id, movie, .. } if *id == movie_id => {
+                    movie.version = version;
+                },
+                _ => {},
+            }
+        }
+        self.save();
+    }
+
+    /// Applies queued changes to movies from the server, so they don't disappear before they are synced.
+    pub fn apply(&self, movies: &mut BTreeMap<MovieId, Movie>) {
+        for request in &self.requests {
+            request.apply(movies);
+        }
+    }
+
+    fn save(&self) {
+        if let Err(error) = LocalStorage::insert(QUEUE_STORAGE_KEY, &self.requests) {
+            error!("Cannot save the sync queue:", error);
+        }
+    }
+}
+
+// ------ movie cache ------
+
+pub fn load_cached_movies() -> Option<BTreeMap<MovieId, Movie>> {
+    LocalStorage::get(MOVIES_STORAGE_KEY).ok()
+}
+
+pub fn cache_movies(movies: &BTreeMap<MovieId, Movie>) {
+    if let Err(error) = LocalStorage::insert(MOVIES_STORAGE_KEY, movies) {
+        error!("Cannot cache movies:", error);
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn queue(requests: Vec<Request>) -> SyncQueue {
+        SyncQueue { requests: requests.into(), offline: false, replaying: false, failed_replays: 0 }
+    }
+
+    fn movie(title: &str, version: u64) -> Movie {
+        Movie { title: title.to_owned(), version, ..Movie::default() }
+    }
+
+    fn versions(queue: &SyncQueue) -> Vec<u64> {
+        queue
+            .requests
+            .iter()
+            .filter_map(|request| match request {
+                Request::Update(_, movie) | Request::Merge { movie, .. } => Some(movie.version),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn retry_delay_doubles_up_to_a_minute() {
+        let mut queue = queue(Vec::new());
+        let delays = (0..9).map(|_| queue.next_retry_delay()).collect::<Vec<_>>();
+        assert_eq!(delays, vec![1_000, 2_000, 4_000, 8_000, 16_000, 32_000, 60_000, 60_000, 60_000]);
+
+        // Long outages don't overflow the delay.
+        for _ in 0..100 {
+            assert_eq!(queue.next_retry_delay(), MAX_RETRY_DELAY_MS);
+        }
+        queue.reset_retry_delay();
+        assert_eq!(queue.next_retry_delay(), MIN_RETRY_DELAY_MS);
+    }
+
+    #[wasm_bindgen_test]
+    fn queued_requests_are_applied_in_order() {
+        let ids = (1..=5).map(MovieId::from).collect::<Vec<_>>();
+        let queue = queue(vec![
+            Request::Update(ids[0], movie("Alien (director's cut)", 2)),
+            Request::Delete(ids[1]),
+            Request::Create(ids[3], movie("Heat", 0)),
+            Request::Merge { surviving_id: ids[2], merged_id: ids[3], movie: movie("Heat (1995)", 4) },
+            Request::CreateMany(vec![(ids[4], movie("Ran", 0))]),
+            Request::AddToList(ListId::from(1), vec![ids[0]]),
+        ]);
+        let mut movies = vec![(ids[0], movie("Alien", 1)), (ids[1], movie("Brazil", 1)), (ids[2], movie("Heat", 3))]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+
+        queue.apply(&mut movies);
+
+        let expected = vec![
+            (ids[0], movie("Alien (director's cut)", 2)),
+            (ids[2], movie("Heat (1995)", 4)),
+            (ids[4], movie("Ran", 0)),
+        ];
+        assert!(movies.into_iter().collect::<Vec<_>>() == expected);
+    }
+
+    #[wasm_bindgen_test]
+    fn set_version_updates_queued_changes_of_the_movie() {
+        let (movie_id, other_id) = (MovieId::from(1), MovieId::from(2));
+        let mut queue = queue(vec![
+            Request::Update(movie_id, movie("Alien", 1)),
+            Request::Update(other_id, movie("Brazil", 1)),
+            Request::Merge { surviving_id: movie_id, merged_id: other_id, movie: movie("Alien", 1) },
+            Request::Merge { surviving_id: other_id, merged_id: movie_id, movie: movie("Brazil", 1) },
+        ]);
+
+        queue.set_version(movie_id, 7);
+
+        assert_eq!(versions(&queue), vec![7, 1, 7, 1]);
+    }
+
+    #[wasm_bindgen_test]
+    fn requests_are_queued_while_offline_or_behind_queued_ones() {
+        let mut queue = queue(Vec::new());
+        assert!(not(queue.should_queue()));
+
+        queue.offline = true;
+        assert!(queue.should_queue());
+
+        queue.offline = false;
+        queue.requests.push_back(Request::Delete(MovieId::from(1)));
+        assert!(queue.should_queue());
+    }
+}
@@ -0,0 +1,96 @@
+use std::ops::Range;
+
+/// Windowing for long lists with fixed row heights.
+/// Only rows in the viewport (plus `overscan` rows on both sides) are rendered,
+/// so the view cost depends on the viewport size instead of the number of rows.
+pub struct VirtualList {
+    pub row_height: f64,
+    pub overscan: usize,
+    pub scroll_top: f64,
+    pub viewport_height: f64,
+}
+
+impl VirtualList {
+    pub const fn new(row_height: f64, overscan: usize, viewport_height: f64) -> Self {
+        Self {
+            row_height,
+            overscan,
+            scroll_top: 0.,
+            viewport_height,
+        }
+    }
+
+    /// Indices of rows that should be rendered.
+    pub fn visible_range(&self, row_count: usize) -> Range<usize> {
+        let first_visible = (self.scroll_top.max(0.) / self.row_height).floor() as usize;
+        let visible_count = (self.viewport_height.max(0.) / self.row_height).ceil() as usize + 1;
+
+        let start = first_visible.saturating_sub(self.overscan).min(row_count);
+        let end = (first_visible + visible_count + self.overscan).min(row_count);
+        start..end
+    }
+
+    pub fn offset_top(&self, row_index: usize) -> f64 {
+        row_index as f64 * self.row_height
+    }
+
+    pub fn total_height(&self, row_count: usize) -> f64 {
+        self.offset_top(row_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn virtual_list(scroll_top: f64) -> VirtualList {
+        VirtualList { scroll_top, ..VirtualList::new(100., 2, 450.) }
+    }
+
+    #[test]
+    fn visible_range_at_the_top() {
+        // 5 rows cover 450px, plus one partially visible row and 2 overscan rows.
+        assert_eq!(virtual_list(0.).visible_range(1000), 0..8);
+    }
+
+    #[test]
+    fn visible_range_in_the_middle() {
+        assert_eq!(virtual_list(1050.).visible_range(1000), 8..18);
+    }
+
+    #[test]
+    fn visible_range_is_clamped_to_row_count() {
+        assert_eq!(virtual_list(0.).visible_range(3), 0..3);
+        assert_eq!(virtual_list(950.).visible_range(10), 7..10);
+        assert_eq!(virtual_list(0.).visible_range(0), 0..0);
+    }
+
+    #[test]
+    fn visible_range_past_the_end_is_empty() {
+        // E.g. a stale scroll offset after the list has been filtered.
+        assert!(virtual_list(50_000.).visible_range(10).is_empty());
+    }
+
+    #[test]
+    fn negative_scroll_top_is_ignored() {
+        // Elastic scrolling reports negative offsets.
+        assert_eq!(virtual_list(-80.).visible_range(1000), virtual_list(0.).visible_range(1000));
+    }
+
+    #[test]
+    fn offsets_and_total_height() {
+        let virtual_list = virtual_list(0.);
+        assert_eq!(virtual_list.offset_top(0), 0.);
+        assert_eq!(virtual_list.offset_top(7), 700.);
+        assert_eq!(virtual_list.total_height(0), 0.);
+        assert_eq!(virtual_list.total_height(1000), 100_000.);
+    }
+
+    #[test]
+    fn rendered_rows_do_not_depend_on_row_count() {
+        for row_count in &[1_000, 100_000, 10_000_000] {
+            let middle = virtual_list(100. * (*row_count / 2) as f64);
+            assert_eq!(middle.visible_range(*row_count).len(), 10);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchStatus {
+    WantToWatch,
+    Watching,
+    Watched,
+    Abandoned,
+}
+
+impl Default for WatchStatus {
+    fn default() -> Self {
+        Self::WantToWatch
+    }
+}
+
+impl WatchStatus {
+    pub const ALL: [Self; 4] = [Self::WantToWatch, Self::Watching, Self::Watched, Self::Abandoned];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::WantToWatch => "want_to_watch",
+            Self::Watching => "watching",
+            Self::Watched => "watched",
+            Self::Abandoned => "abandoned",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|status| status.as_str() == text)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::WantToWatch => "Want to watch",
+            Self::Watching => "Watching",
+            Self::Watched => "Watched",
+            Self::Abandoned => "Abandoned",
+        }
+    }
+
+    /// Statuses reachable from `self`.
+    /// `Watched -> Watched` is allowed - it's a rewatch.
+    pub fn transitions(self) -> &'static [Self] {
+        match self {
+            Self::WantToWatch => &[Self::Watching, Self::Watched, Self::Abandoned],
+            Self::Watching => &[Self::Watched, Self::Abandoned, Self::WantToWatch],
+            Self::Watched => &[Self::Watched, Self::Watching, Self::WantToWatch],
+            Self::Abandoned => &[Self::Watching, Self::WantToWatch],
+        }
+    }
+
+    pub fn can_change_to(self, status: Self) -> bool {
+        self.transitions().contains(&status)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct WatchEvent {
+    pub watched_on: NaiveDate,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_values_only() {
+        for status in WatchStatus::ALL.iter().copied() {
+            assert!(WatchStatus::parse(status.as_str()) == Some(status));
+        }
+        assert!(WatchStatus::parse("Want to watch").is_none());
+        assert!(WatchStatus::parse("").is_none());
+    }
+
+    #[test]
+    fn only_watched_movies_can_be_rewatched() {
+        assert!(WatchStatus::Watched.can_change_to(WatchStatus::Watched));
+        assert!(!WatchStatus::WantToWatch.can_change_to(WatchStatus::WantToWatch));
+        assert!(!WatchStatus::Watching.can_change_to(WatchStatus::Watching));
+        assert!(!WatchStatus::Abandoned.can_change_to(WatchStatus::Abandoned));
+    }
+
+    #[test]
+    fn abandoned_and_watched_movies_cannot_skip_back() {
+        assert!(!WatchStatus::Abandoned.can_change_to(WatchStatus::Watched));
+        assert!(!WatchStatus::Watched.can_change_to(WatchStatus::Abandoned));
+        assert!(WatchStatus::Abandoned.can_change_to(WatchStatus::Watching));
+        assert!(WatchStatus::Watched.can_change_to(WatchStatus::Watching));
+    }
+
+    #[test]
+    fn every_status_can_go_back_to_want_to_watch_and_be_watched() {
+        for status in WatchStatus::ALL.iter().copied().filter(|status| *status != WatchStatus::WantToWatch) {
+            assert!(status.can_change_to(WatchStatus::WantToWatch));
+        }
+        for status in WatchStatus::ALL.iter().copied().filter(|status| *status != WatchStatus::Watching) {
+            assert!(status.can_change_to(WatchStatus::Watching));
+        }
+    }
+}